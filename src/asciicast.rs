@@ -0,0 +1,83 @@
+use crate::prelude::*;
+use crate::terminal::ScreenWriter;
+use std::io::Write;
+
+/// writes frames to an asciicast v2 recording, which asciinema can play back. Every frame only
+/// holds the changes to the previous one, exactly as they would be drawn to the terminal
+pub struct CastWriter<W: Write> {
+    out: W,
+    screen_writer: ScreenWriter,
+}
+
+impl<W: Write> CastWriter<W> {
+    /// starts a recording of a terminal of `width` x `height` cells by writing its header
+    pub fn new(mut out: W, width: usize, height: usize) -> std::io::Result<CastWriter<W>> {
+        writeln!(
+            out,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}}}",
+            width, height
+        )?;
+        Ok(CastWriter {
+            out,
+            screen_writer: ScreenWriter::new(),
+        })
+    }
+    /// records raw terminal output at `time` seconds from the start of the recording
+    pub fn write_output(&mut self, time: f32, data: &str) -> std::io::Result<()> {
+        writeln!(self.out, "[{:.6}, \"o\", {}]", time, json_string(data))
+    }
+    /// records the screen buffer being drawn at `time` seconds from the start of the recording
+    pub fn write_frame(&mut self, time: f32, screen_buffer: &ScreenBuffer) -> std::io::Result<()> {
+        let mut data = vec![];
+        self.screen_writer.draw(&mut data, screen_buffer)?;
+        let data = String::from_utf8(data).expect("the cells and escape codes are valid UTF-8");
+        self.write_output(time, &data)
+    }
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// `text` as a JSON string literal, with its quotes
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod test_asciicast {
+    use super::*;
+    #[test]
+    fn test_json_string_escapes_control_characters() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(json_string("\x1b[0m█"), "\"\\u001b[0m█\"");
+    }
+    #[test]
+    fn test_cast_has_header_and_timed_frames() {
+        let mut cast = CastWriter::new(vec![], 4, 2).unwrap();
+        let mut screen_buffer = ScreenBuffer::new(4, 2);
+        cast.write_frame(0., &screen_buffer).unwrap();
+        screen_buffer.set(1, 2, '#');
+        cast.write_frame(0.02, &screen_buffer).unwrap();
+        let recording = String::from_utf8(cast.out).unwrap();
+        let lines: Vec<&str> = recording.lines().collect();
+        assert_eq!(lines[0], "{\"version\": 2, \"width\": 4, \"height\": 2}");
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("[0.000000, \"o\", \""));
+        assert!(lines[2].starts_with("[0.020000, \"o\", \""));
+        assert!(lines[2].contains('#'));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::prelude::*;
+
+/// how far a single key press turns a camera, in radians
+const ANGULAR_STEP: f32 = 0.05;
+/// how far a single key press moves a free-fly camera
+const LINEAR_STEP: f32 = 0.25;
+/// the factor a single key press scales the distance of an orbit camera to its target by
+const ZOOM_FACTOR: f32 = 0.9;
+const MIN_ORBIT_DISTANCE: f32 = 0.5;
+/// keeps the pitch away from the poles, where the viewing direction becomes parallel to `up`
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// a single discrete camera movement, usually triggered by a key press
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraMotion {
+    YawLeft,
+    YawRight,
+    PitchUp,
+    PitchDown,
+    Forward,
+    Backward,
+    StrafeLeft,
+    StrafeRight,
+    Rise,
+    Sink,
+}
+
+/// something which moves in response to `CameraMotion`s and produces the camera to render with
+pub trait CameraController {
+    fn apply(&mut self, motion: CameraMotion);
+    fn camera(&self) -> Camera;
+}
+
+/// the direction a camera with the given yaw and pitch looks in. Yaw is measured from the y axis
+/// towards the x axis, and a positive pitch looks downwards
+fn viewing_direction(yaw: f32, pitch: f32) -> Vector {
+    vector!(
+        yaw.sin() * pitch.cos(),
+        yaw.cos() * pitch.cos(),
+        -pitch.sin()
+    )
+}
+
+/// a camera which circles around `target`, always facing it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Vector,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl OrbitCamera {
+    pub fn new(target: Vector, distance: f32) -> OrbitCamera {
+        OrbitCamera {
+            target,
+            distance,
+            yaw: 0.,
+            pitch: 0.,
+        }
+    }
+    pub fn position(&self) -> Vector {
+        self.target - self.distance * viewing_direction(self.yaw, self.pitch)
+    }
+    /// a free-fly camera at the same place, looking in the same direction
+    pub fn to_fly_camera(&self) -> FlyCamera {
+        FlyCamera {
+            position: self.position(),
+            yaw: self.yaw,
+            pitch: self.pitch,
+        }
+    }
+}
+
+impl CameraController for OrbitCamera {
+    fn apply(&mut self, motion: CameraMotion) {
+        match motion {
+            CameraMotion::YawLeft | CameraMotion::StrafeLeft => self.yaw += ANGULAR_STEP,
+            CameraMotion::YawRight | CameraMotion::StrafeRight => self.yaw -= ANGULAR_STEP,
+            CameraMotion::PitchUp | CameraMotion::Rise => self.pitch += ANGULAR_STEP,
+            CameraMotion::PitchDown | CameraMotion::Sink => self.pitch -= ANGULAR_STEP,
+            CameraMotion::Forward => self.distance *= ZOOM_FACTOR,
+            CameraMotion::Backward => self.distance /= ZOOM_FACTOR,
+        }
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        self.distance = self.distance.max(MIN_ORBIT_DISTANCE);
+    }
+    fn camera(&self) -> Camera {
+        Camera::look_at(self.position(), self.target, unit_z())
+    }
+}
+
+/// a camera which moves freely through the scene, steered by its yaw and pitch
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlyCamera {
+    pub position: Vector,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl FlyCamera {
+    pub fn new(position: Vector) -> FlyCamera {
+        FlyCamera {
+            position,
+            yaw: 0.,
+            pitch: 0.,
+        }
+    }
+    fn forward(&self) -> Vector {
+        viewing_direction(self.yaw, self.pitch)
+    }
+    fn right(&self) -> Vector {
+        viewing_direction(self.yaw + std::f32::consts::FRAC_PI_2, 0.)
+    }
+}
+
+impl CameraController for FlyCamera {
+    fn apply(&mut self, motion: CameraMotion) {
+        match motion {
+            CameraMotion::YawLeft => self.yaw -= ANGULAR_STEP,
+            CameraMotion::YawRight => self.yaw += ANGULAR_STEP,
+            CameraMotion::PitchUp => self.pitch -= ANGULAR_STEP,
+            CameraMotion::PitchDown => self.pitch += ANGULAR_STEP,
+            CameraMotion::Forward => self.position += LINEAR_STEP * self.forward(),
+            CameraMotion::Backward => self.position -= LINEAR_STEP * self.forward(),
+            CameraMotion::StrafeLeft => self.position -= LINEAR_STEP * self.right(),
+            CameraMotion::StrafeRight => self.position += LINEAR_STEP * self.right(),
+            CameraMotion::Rise => self.position += LINEAR_STEP * unit_z(),
+            CameraMotion::Sink => self.position -= LINEAR_STEP * unit_z(),
+        }
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+    }
+    fn camera(&self) -> Camera {
+        Camera::look_at(self.position, self.position + self.forward(), unit_z())
+    }
+}
+
+#[cfg(test)]
+mod test_camera_controller {
+    use super::*;
+    #[test]
+    fn test_look_at() {
+        let (position, target) = (vector!(-5, -9, 4), vector!(1, 2, -1));
+        let camera = Camera::look_at(position, target, unit_z());
+        let viewport = Viewport::new(21, 9);
+        let central_ray = camera.get_ray_from_camera(&viewport, 4, 10);
+        assert!((central_ray - (target - position).normalise()).norm() < 1e-5);
+        assert!((camera.matrix.transpose() * camera.matrix - Matrix::identity()).norm() < 1e-5);
+        assert!((camera.matrix.determinant() - 1.).abs() < 1e-5);
+        // the screen's up is on the side of the world's up, and its right is horizontal
+        assert!(camera.matrix.column(2).z > 0.);
+        assert!(camera.matrix.column(0).z.abs() < 1e-5);
+    }
+    #[test]
+    fn test_orbit_camera_stays_away_from_poles_and_target() {
+        let mut orbit = OrbitCamera::new(vector!(0, 0, 1), 2.);
+        for _ in 0..100 {
+            orbit.apply(CameraMotion::PitchUp);
+            orbit.apply(CameraMotion::Forward);
+        }
+        assert_eq!(orbit.pitch, MAX_PITCH);
+        assert_eq!(orbit.distance, MIN_ORBIT_DISTANCE);
+        for _ in 0..200 {
+            orbit.apply(CameraMotion::PitchDown);
+        }
+        assert_eq!(orbit.pitch, -MAX_PITCH);
+        orbit.apply(CameraMotion::YawLeft);
+        assert!(((orbit.position() - orbit.target).norm() - MIN_ORBIT_DISTANCE).abs() < 1e-5);
+        assert!(orbit.camera().matrix.iter().all(|value| value.is_finite()));
+    }
+    #[test]
+    fn test_orbit_camera_faces_its_target() {
+        let mut orbit = OrbitCamera::new(vector!(1, 2, 3), 10.);
+        orbit.apply(CameraMotion::YawRight);
+        orbit.apply(CameraMotion::PitchUp);
+        orbit.apply(CameraMotion::Backward);
+        assert!(((orbit.position() - orbit.target).norm() - 10. / ZOOM_FACTOR).abs() < 1e-4);
+        let forward = orbit.camera().matrix.column(1).into_owned();
+        assert!((forward - (orbit.target - orbit.position()).normalise()).norm() < 1e-5);
+    }
+    #[test]
+    fn test_to_fly_camera_keeps_the_view() {
+        let mut orbit = OrbitCamera::new(vector!(1, 2, 3), 10.);
+        orbit.apply(CameraMotion::YawLeft);
+        orbit.apply(CameraMotion::PitchDown);
+        let fly = orbit.to_fly_camera();
+        let (orbit_camera, fly_camera) = (orbit.camera(), fly.camera());
+        assert!((fly_camera.position - orbit_camera.position).norm() < 1e-5);
+        assert!((fly_camera.matrix - orbit_camera.matrix).norm() < 1e-5);
+    }
+    #[test]
+    fn test_fly_camera_moves_along_its_axes() {
+        let mut fly = FlyCamera::new(vector!(0, 0, 0));
+        fly.apply(CameraMotion::Forward);
+        assert!((fly.position - LINEAR_STEP * unit_y()).norm() < 1e-6);
+        fly.apply(CameraMotion::StrafeRight);
+        assert!((fly.position - LINEAR_STEP * vector!(1, 1, 0)).norm() < 1e-6);
+        fly.apply(CameraMotion::Rise);
+        fly.apply(CameraMotion::Backward);
+        fly.apply(CameraMotion::StrafeLeft);
+        assert!((fly.position - LINEAR_STEP * unit_z()).norm() < 1e-6);
+        // turning does not move the camera, and it then moves the way it looks
+        fly.apply(CameraMotion::YawRight);
+        fly.apply(CameraMotion::PitchDown);
+        let start = fly.position;
+        fly.apply(CameraMotion::Forward);
+        let forward = fly.camera().matrix.column(1).into_owned();
+        assert!((fly.position - start - LINEAR_STEP * forward).norm() < 1e-5);
+        assert!(forward.z < 0.);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::prelude::*;
+
+pub const USAGE: &str = "\
+usage: spinner [OPTIONS]
+
+options:
+    --color <none|16|256|truecolor>      colour output mode, `none` by default
+    --mode <ascii|half-block|braille>    how samples are packed into cells, `ascii` by default
+    --ramp <short|long|blocks>           the built-in character ramp, `short` by default
+    --ramp-chars <CHARS>                 a custom character ramp, from darkest to brightest
+    --calibrate                          space the ramp by the measured coverage of its glyphs
+    --gamma <GAMMA>                      gamma applied to the intensity before picking a glyph
+    --contrast <CONTRAST>                contrast applied to the intensity before picking a glyph
+    --dither <none|bayer|floyd-steinberg>
+                                         dithering applied before picking glyphs, `none` by
+                                         default
+    --supersample <COLUMNSxROWS>         fire a grid of rays per sample and average them
+    --adaptive                           only supersample samples on silhouettes and edges
+    --normals <central|tetrahedral|analytic>
+                                         how surface normals are estimated, `analytic` by
+                                         default, falling back to `tetrahedral`
+    --max-iterations <N>                 steps a ray takes before it is counted as a miss
+    --over-relaxation <FACTOR>           multiplier of the ray marching steps, 1 to disable
+    --cutaway                            inside an object, cut it away instead of showing the inner
+                                         side of its surface
+    --image <PATH>                       write one frame to a .png, .ppm or .pgm image and exit
+    --image-size <WIDTHxHEIGHT>          size of the image in pixels, `640x480` by default
+    --cast <PATH>                        record `--duration` seconds to an asciicast file and exit
+    --cast-size <COLUMNSxROWS>           size of the recorded terminal, `80x24` by default
+    --gif <PATH>                         record `--duration` seconds to an animated GIF of
+                                         `--image-size` and exit
+    --duration <SECONDS>                 simulated seconds to record, 10 by default
+    --threads <N>                        number of rendering threads, all cores by default
+    --background                         fill the cells with the colour instead of colouring the
+                                         characters
+    -h, --help                           print this message";
+
+/// the size in pixels of the images written with `--image`, unless `--image-size` is given
+pub const DEFAULT_IMAGE_SIZE: (usize, usize) = (640, 480);
+
+/// the size in cells of the terminal recorded with `--cast`, unless `--cast-size` is given
+pub const DEFAULT_CAST_SIZE: (usize, usize) = (80, 24);
+
+/// the simulated seconds recorded, unless `--duration` is given
+pub const DEFAULT_DURATION: f32 = 10.;
+
+/// the flags which take no value, so `--flag=value` is an error rather than `--flag`
+const SWITCHES: [&str; 6] = [
+    "--background",
+    "--adaptive",
+    "--cutaway",
+    "--calibrate",
+    "-h",
+    "--help",
+];
+
+/// the options the `spinner` binary is run with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    pub renderer: Renderer,
+    pub presenter: CellPresenter,
+    pub normals: NormalEstimator,
+    pub march: MarchSettings,
+    /// where to write a single frame as an image instead of running in the terminal
+    pub image: Option<std::path::PathBuf>,
+    pub image_size: Option<(usize, usize)>,
+    /// where to record an asciicast of the simulation instead of running in the terminal
+    pub cast: Option<std::path::PathBuf>,
+    pub cast_size: Option<(usize, usize)>,
+    /// where to record an animated GIF of the simulation instead of running in the terminal
+    pub gif: Option<std::path::PathBuf>,
+    /// the simulated seconds recorded
+    pub duration: Option<f32>,
+    pub help: bool,
+}
+
+impl Options {
+    /// parses the command line arguments, without the program name. Flags taking a value accept
+    /// both `--flag value` and `--flag=value`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        // the ramp options are collected first and applied together, so they can come in any
+        // order
+        let mut ramp = CharRamp::default();
+        let (mut gamma, mut contrast) = (ramp.gamma, ramp.contrast);
+        let mut calibrate = false;
+        let mut supersampling_grid = None;
+        let mut adaptive = false;
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            if inline_value.is_some() && SWITCHES.contains(&flag.as_str()) {
+                return Err(format!("`{}` does not take a value", flag));
+            }
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or(format!("`{}` needs a value", flag))
+            };
+            match flag.as_str() {
+                "--color" => options.presenter.color_output.mode = parse_color_mode(&value()?)?,
+                "--background" => options.presenter.color_output.target = ColorTarget::Background,
+                "--mode" => options.presenter.mode = parse_render_mode(&value()?)?,
+                "--dither" => options.presenter.dithering = parse_dithering(&value()?)?,
+                "--supersample" => supersampling_grid = Some(parse_grid(&value()?)?),
+                "--adaptive" => adaptive = true,
+                "--normals" => options.normals = parse_normal_estimator(&value()?)?,
+                "--max-iterations" => {
+                    options.march.max_iterations = parse_nonzero_count(&flag, &value()?)?
+                }
+                "--over-relaxation" => {
+                    options.march.over_relaxation = parse_at_least_one(&flag, &value()?)?
+                }
+                "--cutaway" => options.march.interior = InteriorView::Cutaway,
+                "--image" => options.image = Some(value()?.into()),
+                "--image-size" => options.image_size = Some(parse_grid(&value()?)?),
+                "--cast" => options.cast = Some(value()?.into()),
+                "--cast-size" => options.cast_size = Some(parse_grid(&value()?)?),
+                "--gif" => options.gif = Some(value()?.into()),
+                "--duration" => options.duration = Some(parse_positive(&flag, &value()?)?),
+                "--threads" => options.renderer.threads = parse_count(&flag, &value()?)?,
+                "--ramp" => ramp = parse_ramp(&value()?)?,
+                "--ramp-chars" => ramp = parse_custom_ramp(&value()?)?,
+                "--calibrate" => calibrate = true,
+                "--gamma" => gamma = parse_positive(&flag, &value()?)?,
+                "--contrast" => contrast = parse_finite(&flag, &value()?)?,
+                "-h" | "--help" => options.help = true,
+                _ => return Err(format!("unknown option `{}`", flag)),
+            }
+        }
+        options.renderer.supersampling = match (supersampling_grid, adaptive) {
+            (None, false) => Supersampling::Off,
+            (None, true) => Supersampling::Adaptive {
+                columns: 2,
+                rows: 2,
+            },
+            (Some((columns, rows)), false) => Supersampling::Grid { columns, rows },
+            (Some((columns, rows)), true) => Supersampling::Adaptive { columns, rows },
+        };
+        if calibrate {
+            ramp = ramp.calibrated();
+        }
+        options.presenter.ramp = ramp.with_gamma(gamma).with_contrast(contrast);
+        let outputs: Vec<&str> = [
+            ("--image", options.image.is_some()),
+            ("--cast", options.cast.is_some()),
+            ("--gif", options.gif.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(flag, given)| given.then_some(flag))
+        .collect();
+        if outputs.len() > 1 {
+            return Err(format!(
+                "only one of `--image`, `--cast` and `--gif` can be given, got `{}`",
+                outputs.join("`, `")
+            ));
+        }
+        Ok(options)
+    }
+}
+
+fn parse_color_mode(value: &str) -> Result<ColorMode, String> {
+    match value {
+        "none" | "mono" => Ok(ColorMode::Monochrome),
+        "16" => Ok(ColorMode::Ansi16),
+        "256" => Ok(ColorMode::Ansi256),
+        "truecolor" | "24bit" => Ok(ColorMode::TrueColor),
+        _ => Err(format!("unknown colour mode `{}`", value)),
+    }
+}
+
+fn parse_render_mode(value: &str) -> Result<RenderMode, String> {
+    match value {
+        "ascii" => Ok(RenderMode::Ascii),
+        "half-block" | "halfblock" => Ok(RenderMode::HalfBlock),
+        "braille" => Ok(RenderMode::Braille),
+        _ => Err(format!("unknown render mode `{}`", value)),
+    }
+}
+
+fn parse_dithering(value: &str) -> Result<Dithering, String> {
+    match value {
+        "none" => Ok(Dithering::None),
+        "bayer" | "ordered" => Ok(Dithering::Bayer),
+        "floyd-steinberg" | "fs" => Ok(Dithering::FloydSteinberg),
+        _ => Err(format!("unknown dithering `{}`", value)),
+    }
+}
+
+/// parses a grid or image size of the form `2x3`
+fn parse_grid(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("expected a size like `2x2`, got `{}`", value);
+    let (columns, rows) = value.split_once('x').ok_or_else(invalid)?;
+    match (columns.parse(), rows.parse()) {
+        (Ok(columns), Ok(rows)) if columns > 0 && rows > 0 => Ok((columns, rows)),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_normal_estimator(value: &str) -> Result<NormalEstimator, String> {
+    match value {
+        "central" => Ok(NormalEstimator::CentralDifferences),
+        "tetrahedral" => Ok(NormalEstimator::Tetrahedral),
+        "analytic" => Ok(NormalEstimator::Analytic),
+        _ => Err(format!("unknown normal estimator `{}`", value)),
+    }
+}
+
+fn parse_count<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{}` expects a whole number, got `{}`", flag, value))
+}
+
+fn parse_nonzero_count(flag: &str, value: &str) -> Result<u32, String> {
+    match parse_count(flag, value)? {
+        0 => Err(format!("`{}` expects at least 1, got `{}`", flag, value)),
+        count => Ok(count),
+    }
+}
+
+fn parse_ramp(value: &str) -> Result<CharRamp, String> {
+    match value {
+        "short" => Ok(CharRamp::short()),
+        "long" => Ok(CharRamp::long()),
+        "blocks" => Ok(CharRamp::blocks()),
+        _ => Err(format!("unknown character ramp `{}`", value)),
+    }
+}
+
+fn parse_custom_ramp(value: &str) -> Result<CharRamp, String> {
+    if value.is_empty() {
+        return Err("the character ramp needs at least one glyph".to_string());
+    }
+    Ok(CharRamp::from_glyphs(value))
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<f32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{}` expects a number, got `{}`", flag, value))
+}
+
+fn parse_finite(flag: &str, value: &str) -> Result<f32, String> {
+    match parse_number(flag, value)? {
+        number if number.is_finite() => Ok(number),
+        _ => Err(format!(
+            "`{}` expects a finite number, got `{}`",
+            flag, value
+        )),
+    }
+}
+
+/// a number greater than 0, which also rules out NaN
+fn parse_positive(flag: &str, value: &str) -> Result<f32, String> {
+    match parse_number(flag, value)? {
+        number if number > 0. && number.is_finite() => Ok(number),
+        _ => Err(format!(
+            "`{}` expects a positive number, got `{}`",
+            flag, value
+        )),
+    }
+}
+
+/// a finite number of at least 1, like the over-relaxation factor, which is 1 when disabled
+fn parse_at_least_one(flag: &str, value: &str) -> Result<f32, String> {
+    match parse_number(flag, value)? {
+        number if number >= 1. && number.is_finite() => Ok(number),
+        _ => Err(format!(
+            "`{}` expects a number of at least 1, got `{}`",
+            flag, value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test_cli {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+    #[test]
+    fn test_duration_must_be_positive() {
+        assert_eq!(parse(&["--duration", "2.5"]).unwrap().duration, Some(2.5));
+        for duration in ["0", "-1", "NaN", "inf", "soon"] {
+            assert!(parse(&["--duration", duration]).is_err(), "{}", duration);
+        }
+    }
+    #[test]
+    fn test_only_one_output_file() {
+        assert!(parse(&["--cast", "a.cast", "--duration=3"]).is_ok());
+        let error = parse(&["--image", "a.png", "--gif", "a.gif"]).unwrap_err();
+        assert!(error.contains("`--image`, `--gif`"), "{}", error);
+        assert!(parse(&["--cast", "a.cast", "--gif", "a.gif"]).is_err());
+    }
+    #[test]
+    fn test_march_settings_are_in_range() {
+        let options = parse(&["--max-iterations", "40", "--over-relaxation=1.5"]).unwrap();
+        assert_eq!(options.march.max_iterations, 40);
+        assert_eq!(options.march.over_relaxation, 1.5);
+        assert_eq!(
+            parse(&["--over-relaxation", "1"])
+                .unwrap()
+                .march
+                .over_relaxation,
+            1.
+        );
+        for factor in ["0", "0.9", "-2", "NaN", "inf"] {
+            assert!(parse(&["--over-relaxation", factor]).is_err(), "{}", factor);
+        }
+        for iterations in ["0", "-1"] {
+            assert!(
+                parse(&["--max-iterations", iterations]).is_err(),
+                "{}",
+                iterations
+            );
+        }
+    }
+    #[test]
+    fn test_gamma_and_contrast_are_valid() {
+        let options = parse(&["--gamma", "2.2", "--contrast", "-0.5"]).unwrap();
+        assert_eq!(options.presenter.ramp.gamma, 2.2);
+        assert_eq!(options.presenter.ramp.contrast, -0.5);
+        for gamma in ["0", "-1", "NaN", "inf"] {
+            assert!(parse(&["--gamma", gamma]).is_err(), "{}", gamma);
+        }
+        for contrast in ["NaN", "inf", "-inf"] {
+            assert!(parse(&["--contrast", contrast]).is_err(), "{}", contrast);
+        }
+    }
+    #[test]
+    fn test_switches_take_no_value() {
+        let options = parse(&["--adaptive", "--calibrate"]).unwrap();
+        assert!(matches!(
+            options.renderer.supersampling,
+            Supersampling::Adaptive { .. }
+        ));
+        for switch in [
+            "--adaptive=false",
+            "--background=1",
+            "--cutaway=no",
+            "--help=yes",
+        ] {
+            assert!(parse(&[switch]).is_err(), "{}", switch);
+        }
+    }
+}
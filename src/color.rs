@@ -0,0 +1,171 @@
+use crossterm::style::Color;
+
+/// a colour with linear channels between 0 and 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Rgb {
+    pub const WHITE: Rgb = Rgb::new(1., 1., 1.);
+    pub const BLACK: Rgb = Rgb::new(0., 0., 0.);
+
+    pub const fn new(r: f32, g: f32, b: f32) -> Rgb {
+        Rgb { r, g, b }
+    }
+    pub fn from_u8(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb::new(r as f32 / 255., g as f32 / 255., b as f32 / 255.)
+    }
+    /// multiplies every channel by `factor`, clamping the result to the valid range
+    pub fn scale(self, factor: f32) -> Rgb {
+        Rgb::new(
+            (self.r * factor).clamp(0., 1.),
+            (self.g * factor).clamp(0., 1.),
+            (self.b * factor).clamp(0., 1.),
+        )
+    }
+    pub fn to_u8(self) -> (u8, u8, u8) {
+        let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+        (channel(self.r), channel(self.g), channel(self.b))
+    }
+    fn distance_squared(&self, other: &Rgb) -> f32 {
+        (self.r - other.r).powi(2) + (self.g - other.g).powi(2) + (self.b - other.b).powi(2)
+    }
+}
+
+/// how many colours the terminal can display
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// no colour escape codes at all, only the brightness of the characters
+    Monochrome,
+    /// the 16 standard ANSI colours
+    Ansi16,
+    /// the 256-colour xterm palette
+    Ansi256,
+    /// 24-bit RGB
+    TrueColor,
+}
+
+/// the part of the terminal cell the colour is applied to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorTarget {
+    /// the character from the ramp is drawn in the shaded colour
+    Foreground,
+    /// the whole cell is filled with the shaded colour
+    Background,
+}
+
+/// approximate RGB values of the 16 ANSI colours, as rendered by the xterm default palette
+const ANSI_16_PALETTE: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (205, 0, 0)),
+    (Color::DarkGreen, (0, 205, 0)),
+    (Color::DarkYellow, (205, 205, 0)),
+    (Color::DarkBlue, (0, 0, 238)),
+    (Color::DarkMagenta, (205, 0, 205)),
+    (Color::DarkCyan, (0, 205, 205)),
+    (Color::Grey, (229, 229, 229)),
+    (Color::DarkGrey, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// the channel levels of the 6x6x6 colour cube of the 256-colour palette
+const ANSI_256_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn nearest_cube_level(channel: u8) -> usize {
+    (0..ANSI_256_CUBE_LEVELS.len())
+        .min_by_key(|&index| (ANSI_256_CUBE_LEVELS[index] as i16 - channel as i16).abs())
+        .unwrap()
+}
+
+impl ColorMode {
+    /// the closest colour the terminal can display, or `None` in monochrome mode
+    pub fn to_terminal_color(self, color: Rgb) -> Option<Color> {
+        match self {
+            ColorMode::Monochrome => None,
+            ColorMode::Ansi16 => ANSI_16_PALETTE
+                .iter()
+                .min_by(|(_, a), (_, b)| {
+                    let a = Rgb::from_u8(a.0, a.1, a.2).distance_squared(&color);
+                    let b = Rgb::from_u8(b.0, b.1, b.2).distance_squared(&color);
+                    a.partial_cmp(&b).unwrap()
+                })
+                .map(|(terminal_color, _)| *terminal_color),
+            ColorMode::Ansi256 => {
+                let (r, g, b) = color.to_u8();
+                let (r, g, b) = (
+                    nearest_cube_level(r),
+                    nearest_cube_level(g),
+                    nearest_cube_level(b),
+                );
+                let cube = Rgb::from_u8(
+                    ANSI_256_CUBE_LEVELS[r],
+                    ANSI_256_CUBE_LEVELS[g],
+                    ANSI_256_CUBE_LEVELS[b],
+                );
+                // the 24 greys at the end of the palette go from 8 to 238 in steps of 10
+                let grey_level = ((color.r + color.g + color.b) / 3. * 255.).round();
+                let grey_index = ((grey_level - 8.) / 10.).round().clamp(0., 23.);
+                let grey_value = (8. + 10. * grey_index) / 255.;
+                let grey = Rgb::new(grey_value, grey_value, grey_value);
+                if grey.distance_squared(&color) < cube.distance_squared(&color) {
+                    Some(Color::AnsiValue(232 + grey_index as u8))
+                } else {
+                    Some(Color::AnsiValue((16 + 36 * r + 6 * g + b) as u8))
+                }
+            }
+            ColorMode::TrueColor => {
+                let (r, g, b) = color.to_u8();
+                Some(Color::Rgb { r, g, b })
+            }
+        }
+    }
+}
+
+/// everything needed to turn a shaded colour into terminal escape codes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorOutput {
+    pub mode: ColorMode,
+    pub target: ColorTarget,
+}
+
+impl Default for ColorOutput {
+    fn default() -> ColorOutput {
+        ColorOutput {
+            mode: ColorMode::Monochrome,
+            target: ColorTarget::Foreground,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_color {
+    use super::*;
+    #[test]
+    fn test_ansi_256_cube_and_greys() {
+        assert_eq!(
+            ColorMode::Ansi256.to_terminal_color(Rgb::new(1., 0., 0.)),
+            Some(Color::AnsiValue(196))
+        );
+        assert_eq!(
+            ColorMode::Ansi256.to_terminal_color(Rgb::from_u8(128, 128, 128)),
+            Some(Color::AnsiValue(244))
+        );
+    }
+    #[test]
+    fn test_ansi_16_nearest() {
+        assert_eq!(
+            ColorMode::Ansi16.to_terminal_color(Rgb::from_u8(250, 10, 10)),
+            Some(Color::Red)
+        );
+        assert_eq!(ColorMode::Monochrome.to_terminal_color(Rgb::WHITE), None);
+    }
+}
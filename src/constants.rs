@@ -1,7 +1,6 @@
-/// the horizontal field of view, in radians
-pub const FOV: f32 = 0.8;
-/// the ratio of height to width of a typical terminal
-pub const H_W_RATIO: f32 = 2.0;
-pub const HEIGHT: i32 = 100;
-pub const WIDTH: i32 = 360;
-pub const MAX_ITERATIONS: u16 = 275;
+/// the default horizontal field of view, in radians
+pub const DEFAULT_FOV: f32 = 0.8;
+/// the default ratio of height to width of a terminal cell
+pub const DEFAULT_H_W_RATIO: f32 = 2.0;
+/// the smallest field of view, in radians, along either axis of the terminal or an image
+pub const MIN_FOV: f32 = 0.45;
@@ -0,0 +1,114 @@
+/// the 4x4 Bayer threshold matrix, the thresholds are `(value + 0.5) / 16`
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// how intensities are quantized to the few levels the output can show
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dithering {
+    /// every value is rounded to the closest level, which gives bands on smooth gradients
+    #[default]
+    None,
+    /// values between two levels are rounded up or down following a 4x4 Bayer pattern
+    Bayer,
+    /// the rounding error of every value is spread to its unprocessed neighbours
+    FloydSteinberg,
+}
+
+/// the index of the level closest to `value`. `levels` has to be sorted and non-empty
+pub(crate) fn closest_level(levels: &[f32], value: f32) -> usize {
+    let index = levels.partition_point(|&level| level < value);
+    match index {
+        0 => 0,
+        index if index == levels.len() => index - 1,
+        index if value - levels[index - 1] < levels[index] - value => index - 1,
+        index => index,
+    }
+}
+
+/// the levels directly below and above `value`, or the same level twice outside their range
+fn bracketing_levels(levels: &[f32], value: f32) -> (f32, f32) {
+    let index = levels.partition_point(|&level| level <= value);
+    match index {
+        0 => (levels[0], levels[0]),
+        index if index == levels.len() => (levels[index - 1], levels[index - 1]),
+        index => (levels[index - 1], levels[index]),
+    }
+}
+
+impl Dithering {
+    /// replaces every value of the row-major buffer `values`, `width` values wide, by one of
+    /// `levels`, which have to be sorted and non-empty. `None` values are left alone and do not
+    /// receive any diffused error, so the background does not bleed into the object
+    pub fn quantize(self, values: &mut [Option<f32>], width: usize, levels: &[f32]) {
+        match self {
+            Dithering::None => {
+                for value in values.iter_mut().flatten() {
+                    *value = levels[closest_level(levels, *value)];
+                }
+            }
+            Dithering::Bayer => {
+                for (index, value) in values.iter_mut().enumerate() {
+                    let Some(value) = value else { continue };
+                    let (row, col) = (index / width, index % width);
+                    let threshold = (BAYER_4X4[row % 4][col % 4] as f32 + 0.5) / 16.;
+                    let (below, above) = bracketing_levels(levels, *value);
+                    *value = if above > below && (*value - below) / (above - below) > threshold {
+                        above
+                    } else {
+                        below
+                    };
+                }
+            }
+            Dithering::FloydSteinberg => {
+                let height = values.len() / width.max(1);
+                for row in 0..height {
+                    for col in 0..width {
+                        let Some(value) = values[row * width + col] else {
+                            continue;
+                        };
+                        let quantized = levels[closest_level(levels, value)];
+                        values[row * width + col] = Some(quantized);
+                        let error = value - quantized;
+                        let mut diffuse = |row: usize, col: isize, weight: f32| {
+                            if row >= height || col < 0 || col as usize >= width {
+                                return;
+                            }
+                            if let Some(neighbour) = values[row * width + col as usize].as_mut() {
+                                *neighbour += error * weight;
+                            }
+                        };
+                        let col = col as isize;
+                        diffuse(row, col + 1, 7. / 16.);
+                        diffuse(row + 1, col - 1, 3. / 16.);
+                        diffuse(row + 1, col, 5. / 16.);
+                        diffuse(row + 1, col + 1, 1. / 16.);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_dither {
+    use super::*;
+    #[test]
+    fn test_dithering_preserves_average_brightness() {
+        let width = 16;
+        for dithering in [Dithering::Bayer, Dithering::FloydSteinberg] {
+            let mut values = vec![Some(0.3); width * width];
+            dithering.quantize(&mut values, width, &[0., 1.]);
+            let average: f32 = values.iter().flatten().sum::<f32>() / values.len() as f32;
+            assert!((average - 0.3).abs() < 0.05, "{:?}: {}", dithering, average);
+        }
+        let mut values = vec![Some(0.3); 4];
+        Dithering::None.quantize(&mut values, 2, &[0., 1.]);
+        assert_eq!(values, vec![Some(0.); 4]);
+    }
+    #[test]
+    fn test_background_is_untouched() {
+        let mut values = vec![Some(0.4), None, Some(0.4), None];
+        Dithering::FloydSteinberg.quantize(&mut values, 2, &[0., 1.]);
+        assert_eq!(values[1], None);
+        assert_eq!(values[3], None);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::prelude::*;
+
+/// the output of the ray marcher: the shading of every pixel of a viewport, in row-major order,
+/// or `None` where the ray missed. Presenters turn it into characters, colours or images
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameBuffer {
+    viewport: Viewport,
+    pixels: Vec<Option<Shading>>,
+}
+
+impl FrameBuffer {
+    /// `pixels` has to hold exactly `viewport.width * viewport.height` values
+    pub fn new(viewport: Viewport, pixels: Vec<Option<Shading>>) -> FrameBuffer {
+        assert_eq!(
+            pixels.len(),
+            viewport.width * viewport.height,
+            "the frame buffer does not match the size of its viewport"
+        );
+        FrameBuffer { viewport, pixels }
+    }
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+    pub fn width(&self) -> usize {
+        self.viewport.width
+    }
+    pub fn height(&self) -> usize {
+        self.viewport.height
+    }
+    pub fn get(&self, row: usize, col: usize) -> Option<&Shading> {
+        self.pixels[row * self.viewport.width + col].as_ref()
+    }
+    pub fn pixels(&self) -> &[Option<Shading>] {
+        &self.pixels
+    }
+}
+
+#[cfg(test)]
+mod test_framebuffer {
+    use super::*;
+
+    fn shading(intensity: f32) -> Option<Shading> {
+        Some(Shading {
+            intensity,
+            color: Rgb::WHITE.scale(intensity),
+            normal: unit_z(),
+            depth: 1.,
+            object_id: None,
+        })
+    }
+    #[test]
+    fn test_get_indexes_rows_then_columns() {
+        let pixels = vec![
+            shading(0.1),
+            None,
+            shading(0.3),
+            shading(0.4),
+            shading(0.5),
+            None,
+        ];
+        let frame = FrameBuffer::new(Viewport::new(3, 2), pixels);
+        assert_eq!((frame.width(), frame.height()), (3, 2));
+        assert_eq!(frame.get(0, 2).unwrap().intensity, 0.3);
+        assert_eq!(frame.get(1, 0).unwrap().intensity, 0.4);
+        assert_eq!(frame.get(0, 1), None);
+        assert_eq!(frame.get(1, 2), None);
+    }
+    #[test]
+    #[should_panic(expected = "does not match the size of its viewport")]
+    fn test_new_rejects_pixels_of_the_wrong_size() {
+        FrameBuffer::new(Viewport::new(3, 2), vec![None; 5]);
+    }
+}
@@ -0,0 +1,379 @@
+//! a self-contained encoder of animated GIFs. Every frame gets its own palette of up to 256
+//! colours, picked by median cut, and is compressed with the variable width LZW of the format
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// the most colours a GIF palette holds
+pub const MAX_COLORS: usize = 256;
+/// the size in bits of the palette indices, which start the LZW codes
+const MIN_CODE_SIZE: u8 = 8;
+/// LZW codes are never wider than 12 bits
+const MAX_CODE: u16 = 4095;
+/// the histogram of the quantization keeps 5 bits of every channel
+const HISTOGRAM_BITS: usize = 5;
+
+/// a colour of the histogram, with the number of pixels falling into it and the sums of their
+/// channels, so the palette gets their exact average
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    key: usize,
+    count: u32,
+    sums: [u32; 3],
+}
+
+impl Bucket {
+    /// the value of the channel, from 0 to 31
+    fn channel(&self, channel: usize) -> usize {
+        (self.key >> (HISTOGRAM_BITS * (2 - channel))) & ((1 << HISTOGRAM_BITS) - 1)
+    }
+}
+
+fn histogram_key(r: u8, g: u8, b: u8) -> usize {
+    let shift = 8 - HISTOGRAM_BITS;
+    ((r as usize >> shift) << (2 * HISTOGRAM_BITS))
+        | ((g as usize >> shift) << HISTOGRAM_BITS)
+        | (b as usize >> shift)
+}
+
+/// the widest channel of the buckets, with its range
+fn widest_channel(buckets: &[Bucket]) -> (usize, usize) {
+    (0..3)
+        .map(|channel| {
+            let values = buckets.iter().map(|bucket| bucket.channel(channel));
+            let range = values.clone().max().unwrap_or(0) - values.min().unwrap_or(0);
+            (channel, range)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap()
+}
+
+/// reduces the RGB bytes to at most `max_colors` colours by median cut: the box of colours with
+/// the widest range is split at the median pixel of that channel until there are enough boxes.
+/// Returns the palette and the index into it of every pixel
+pub fn quantize(rgb: &[u8], max_colors: usize) -> (Vec<[u8; 3]>, Vec<u8>) {
+    assert!(
+        (1..=MAX_COLORS).contains(&max_colors),
+        "a palette holds from 1 to 256 colours"
+    );
+    let mut histogram = vec![(0_u32, [0_u32; 3]); 1 << (3 * HISTOGRAM_BITS)];
+    for pixel in rgb.chunks_exact(3) {
+        let (count, sums) = &mut histogram[histogram_key(pixel[0], pixel[1], pixel[2])];
+        *count += 1;
+        for (sum, &value) in sums.iter_mut().zip(pixel) {
+            *sum += value as u32;
+        }
+    }
+    let buckets: Vec<Bucket> = histogram
+        .iter()
+        .enumerate()
+        .filter(|(_, (count, _))| *count > 0)
+        .map(|(key, &(count, sums))| Bucket { key, count, sums })
+        .collect();
+    let mut boxes = vec![buckets];
+    while boxes.len() < max_colors {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, buckets)| buckets.len() > 1)
+            .map(|(box_num, buckets)| (box_num, widest_channel(buckets)))
+            .max_by_key(|&(_, (_, range))| range);
+        let Some((box_num, (channel, _))) = widest else {
+            // every box holds a single colour of the histogram
+            break;
+        };
+        let buckets = &mut boxes[box_num];
+        buckets.sort_unstable_by_key(|bucket| bucket.channel(channel));
+        let total: u32 = buckets.iter().map(|bucket| bucket.count).sum();
+        let mut seen = 0;
+        let median = buckets
+            .iter()
+            .position(|bucket| {
+                seen += bucket.count;
+                2 * seen >= total
+            })
+            .unwrap();
+        let upper = buckets.split_off((median + 1).clamp(1, buckets.len() - 1));
+        boxes.push(upper);
+    }
+    let mut lookup = vec![0_u8; histogram.len()];
+    let palette = boxes
+        .iter()
+        .enumerate()
+        .map(|(index, buckets)| {
+            let count: u32 = buckets.iter().map(|bucket| bucket.count).sum();
+            let mut color = [0_u8; 3];
+            for (channel, value) in color.iter_mut().enumerate() {
+                let sum: u32 = buckets.iter().map(|bucket| bucket.sums[channel]).sum();
+                *value = ((sum + count / 2) / count.max(1)) as u8;
+            }
+            for bucket in buckets {
+                lookup[bucket.key] = index as u8;
+            }
+            color
+        })
+        .collect();
+    let indices = rgb
+        .chunks_exact(3)
+        .map(|pixel| lookup[histogram_key(pixel[0], pixel[1], pixel[2])])
+        .collect();
+    (palette, indices)
+}
+
+/// packs codes of varying width into bytes, least significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    n_bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.buffer |= (code as u32) << self.n_bits;
+        self.n_bits += width;
+        while self.n_bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.n_bits -= 8;
+        }
+    }
+    fn finish(mut self) -> Vec<u8> {
+        if self.n_bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// compresses the palette indices with the LZW variant of GIF, starting from codes of
+/// `min_code_size + 1` bits. The table is cleared whenever it is full
+pub fn lzw_compress(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear_code = 1_u16 << min_code_size;
+    let end_code = clear_code + 1;
+    let initial_width = min_code_size as u32 + 1;
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut width = initial_width;
+    let mut next_code = end_code + 1;
+    writer.write(clear_code, width);
+    let Some((&first, rest)) = indices.split_first() else {
+        writer.write(end_code, width);
+        return writer.finish();
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        writer.write(prefix, width);
+        if next_code <= MAX_CODE {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+            // the decoder adds its entries one code later, so the width grows one code later
+            if next_code > 1 << width && width < 12 {
+                width += 1;
+            }
+        } else {
+            writer.write(clear_code, width);
+            table.clear();
+            width = initial_width;
+            next_code = end_code + 1;
+        }
+        prefix = index as u16;
+    }
+    writer.write(prefix, width);
+    // the decoder adds an entry for the last code too, which may widen the end code
+    if next_code <= MAX_CODE && next_code + 1 > 1 << width && width < 12 {
+        width += 1;
+    }
+    writer.write(end_code, width);
+    writer.finish()
+}
+
+/// writes an animated GIF frame by frame, looping forever. The frames are shown at `fps`, with
+/// the delays of single frames rounded to hundredths of seconds so the total time keeps in step
+pub struct GifWriter<W: Write> {
+    out: W,
+    width: u16,
+    height: u16,
+    fps: u32,
+    frame_count: u32,
+}
+
+impl<W: Write> GifWriter<W> {
+    /// starts an animation of `width` x `height` pixels by writing its header
+    pub fn new(mut out: W, width: usize, height: usize, fps: u32) -> std::io::Result<GifWriter<W>> {
+        let size = |value: usize| {
+            u16::try_from(value).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("a GIF is at most 65535 pixels wide and high, not {}", value),
+                )
+            })
+        };
+        let (width, height) = (size(width)?, size(height)?);
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // no global palette, the background colour and the pixel aspect ratio
+        out.write_all(&[0, 0, 0])?;
+        // the application extension which loops the animation, 0 times meaning forever
+        out.write_all(&[0x21, 0xff, 11])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[3, 1, 0, 0, 0])?;
+        Ok(GifWriter {
+            out,
+            width,
+            height,
+            fps: fps.max(1),
+            frame_count: 0,
+        })
+    }
+    /// how long the next frame is shown, in hundredths of seconds
+    fn next_delay(&self) -> u16 {
+        let centiseconds =
+            |frames: u32| (frames as u64 * 100 + self.fps as u64 / 2) / self.fps as u64;
+        (centiseconds(self.frame_count + 1) - centiseconds(self.frame_count)) as u16
+    }
+    /// adds the image as the next frame of the animation
+    pub fn write_frame(&mut self, image: &Image) -> std::io::Result<()> {
+        if (image.width(), image.height()) != (self.width as usize, self.height as usize) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "the frame is {}x{}, the animation {}x{}",
+                    image.width(),
+                    image.height(),
+                    self.width,
+                    self.height
+                ),
+            ));
+        }
+        let (palette, indices) = quantize(&image.rgb_bytes(), MAX_COLORS);
+        // the graphic control extension with the delay, leaving the frame in place afterwards
+        self.out.write_all(&[0x21, 0xf9, 4, 1 << 2])?;
+        self.out.write_all(&self.next_delay().to_le_bytes())?;
+        self.out.write_all(&[0, 0])?;
+        // the image descriptor, covering the whole animation, with a local palette of 256 colours
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&self.width.to_le_bytes())?;
+        self.out.write_all(&self.height.to_le_bytes())?;
+        self.out.write_all(&[0x80 | (MIN_CODE_SIZE - 1)])?;
+        for index in 0..MAX_COLORS {
+            self.out
+                .write_all(&palette.get(index).copied().unwrap_or_default())?;
+        }
+        self.out.write_all(&[MIN_CODE_SIZE])?;
+        for block in lzw_compress(&indices, MIN_CODE_SIZE).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])?;
+        self.frame_count += 1;
+        Ok(())
+    }
+    /// ends the animation and hands back the output
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod test_gif {
+    use super::*;
+
+    /// the palette indices compressed by `lzw_compress`
+    fn lzw_decompress(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear_code = 1_usize << min_code_size;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let (mut width, mut position) = (min_code_size as usize + 1, 0);
+        let mut previous: Option<usize> = None;
+        let mut indices = vec![];
+        loop {
+            let code = (0..width).fold(0, |code, bit| {
+                let bit_num = position + bit;
+                code | (((bytes[bit_num / 8] >> (bit_num % 8)) & 1) as usize) << bit
+            });
+            position += width;
+            if code == clear_code {
+                table = (0..clear_code).map(|index| vec![index as u8]).collect();
+                table.extend([vec![], vec![]]);
+                width = min_code_size as usize + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear_code + 1 {
+                return indices;
+            }
+            let entry = match (table.get(code), previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => {
+                    let mut entry = table[previous].clone();
+                    entry.push(entry[0]);
+                    entry
+                }
+                (None, None) => panic!("unknown code {}", code),
+            };
+            if let Some(previous) = previous {
+                if table.len() < 4096 {
+                    let mut added = table[previous].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    if table.len() == 1 << width && width < 12 {
+                        width += 1;
+                    }
+                }
+            }
+            indices.extend_from_slice(&entry);
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        // long enough to fill the table and clear it several times
+        let mut state = 1_u32;
+        let noise: Vec<u8> = (0..40000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+        let runs: Vec<u8> = (0..5000_u32).map(|i| (i / 7 % 3) as u8).collect();
+        for indices in [vec![], vec![5], runs, noise] {
+            assert_eq!(lzw_decompress(&lzw_compress(&indices, 8), 8), indices);
+        }
+    }
+    #[test]
+    fn test_quantize_keeps_few_colors() {
+        let colors = [[0, 0, 0], [255, 128, 0], [10, 200, 255]];
+        let rgb: Vec<u8> = (0..300).flat_map(|i| colors[i % 3]).collect();
+        let (palette, indices) = quantize(&rgb, MAX_COLORS);
+        assert_eq!(palette.len(), 3);
+        for (pixel, &index) in rgb.chunks(3).zip(&indices) {
+            assert_eq!(palette[index as usize], pixel);
+        }
+        let (palette, indices) = quantize(&rgb, 2);
+        assert_eq!(palette.len(), 2);
+        assert!(indices.iter().all(|&index| index < 2));
+    }
+    #[test]
+    fn test_animation_delays_follow_fps() {
+        let mut gif = GifWriter::new(vec![], 4, 3, 30).unwrap();
+        let mut delays = vec![];
+        for _ in 0..30 {
+            delays.push(gif.next_delay());
+            gif.write_frame(&Image::new(4, 3)).unwrap();
+        }
+        assert!(delays.iter().all(|&delay| delay == 3 || delay == 4));
+        assert_eq!(delays.iter().sum::<u16>(), 100);
+        assert!(gif.write_frame(&Image::new(3, 4)).is_err());
+        let bytes = gif.finish().unwrap();
+        assert!(bytes.starts_with(b"GIF89a\x04\x00\x03\x00"));
+        assert_eq!(bytes.last(), Some(&0x3b));
+    }
+}
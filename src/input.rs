@@ -0,0 +1,111 @@
+use crate::camera_controller::CameraMotion;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::time;
+
+/// the actions the interactive main loop responds to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// leave the main loop and restore the terminal
+    Quit,
+    /// pause or resume the propagation of the physics
+    TogglePause,
+    /// while paused, advance the simulation by a single frame
+    Step,
+    /// put the body back into its initial orientation
+    Reset,
+    /// move the active camera
+    MoveCamera(CameraMotion),
+    /// switch between the orbit and the free-fly camera
+    ToggleCameraMode,
+    /// the terminal was resized to (columns, rows)
+    Resize(u16, u16),
+}
+
+/// maps a key press to the command bound to it, if any
+pub fn command_from_key(key: &KeyEvent) -> Option<Command> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Command::Quit),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Command::Quit),
+        KeyCode::Char(' ') | KeyCode::Char('p') => Some(Command::TogglePause),
+        KeyCode::Char('n') | KeyCode::Char('.') => Some(Command::Step),
+        KeyCode::Char('r') => Some(Command::Reset),
+        KeyCode::Char('f') => Some(Command::ToggleCameraMode),
+        code => camera_motion_from_key(code).map(Command::MoveCamera),
+    }
+}
+
+fn camera_motion_from_key(code: KeyCode) -> Option<CameraMotion> {
+    match code {
+        KeyCode::Left => Some(CameraMotion::YawLeft),
+        KeyCode::Right => Some(CameraMotion::YawRight),
+        KeyCode::Up => Some(CameraMotion::PitchUp),
+        KeyCode::Down => Some(CameraMotion::PitchDown),
+        KeyCode::Char('w') => Some(CameraMotion::Forward),
+        KeyCode::Char('s') => Some(CameraMotion::Backward),
+        KeyCode::Char('a') => Some(CameraMotion::StrafeLeft),
+        KeyCode::Char('d') => Some(CameraMotion::StrafeRight),
+        KeyCode::PageUp => Some(CameraMotion::Rise),
+        KeyCode::PageDown => Some(CameraMotion::Sink),
+        _ => None,
+    }
+}
+
+/// drains the pending terminal events without blocking and returns the commands they map to, in
+/// the order they arrived
+pub fn poll_commands() -> std::io::Result<Vec<Command>> {
+    let mut commands = vec![];
+    while event::poll(time::Duration::ZERO)? {
+        let command = match event::read()? {
+            Event::Key(key) => command_from_key(&key),
+            Event::Resize(columns, rows) => Some(Command::Resize(columns, rows)),
+            _ => None,
+        };
+        commands.extend(command);
+    }
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod test_input {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+    #[test]
+    fn test_key_bindings() {
+        let command = |code| command_from_key(&press(code));
+        assert_eq!(command(KeyCode::Char('q')), Some(Command::Quit));
+        assert_eq!(command(KeyCode::Esc), Some(Command::Quit));
+        assert_eq!(
+            command_from_key(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Command::Quit)
+        );
+        assert_eq!(command(KeyCode::Char('c')), None);
+        assert_eq!(command(KeyCode::Char(' ')), Some(Command::TogglePause));
+        assert_eq!(command(KeyCode::Char('p')), Some(Command::TogglePause));
+        assert_eq!(command(KeyCode::Char('n')), Some(Command::Step));
+        assert_eq!(command(KeyCode::Char('.')), Some(Command::Step));
+        assert_eq!(command(KeyCode::Char('r')), Some(Command::Reset));
+        assert_eq!(command(KeyCode::Char('f')), Some(Command::ToggleCameraMode));
+        assert_eq!(
+            command(KeyCode::Left),
+            Some(Command::MoveCamera(CameraMotion::YawLeft))
+        );
+        assert_eq!(
+            command(KeyCode::Char('w')),
+            Some(Command::MoveCamera(CameraMotion::Forward))
+        );
+        assert_eq!(
+            command(KeyCode::PageDown),
+            Some(Command::MoveCamera(CameraMotion::Sink))
+        );
+        assert_eq!(command(KeyCode::Char('x')), None);
+        let mut release = press(KeyCode::Char('q'));
+        release.kind = KeyEventKind::Release;
+        assert_eq!(command_from_key(&release), None);
+    }
+}
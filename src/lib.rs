@@ -0,0 +1,24 @@
+pub mod asciicast;
+pub mod camera_controller;
+pub mod cli;
+pub mod color;
+pub mod constants;
+pub mod dither;
+pub mod framebuffer;
+pub mod gif;
+pub mod input;
+pub mod lighting;
+pub mod math;
+pub mod normals;
+pub mod objects;
+pub mod offscreen;
+pub mod physics;
+pub mod png;
+pub mod prelude;
+pub mod presenter;
+pub mod ramp;
+pub mod renderer;
+pub mod scene;
+pub mod supersampling;
+pub mod terminal;
+pub mod viewport;
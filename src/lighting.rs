@@ -0,0 +1,334 @@
+use crate::prelude::*;
+
+/// how far along the normal the shadow rays start, so they do not hit the surface they leave from
+const SHADOW_BIAS: f32 = 0.01;
+/// distance to the surface below which a shadow ray counts as blocked
+const SHADOW_HIT_DISTANCE: f32 = 0.001;
+/// how far shadow rays towards directional lights are marched before they count as unblocked
+const MAX_SHADOW_DISTANCE: f32 = 100.;
+const MAX_SHADOW_ITERATIONS: u16 = 128;
+
+/// a source of light in the scene. Intensities are in the same units as the final pixel
+/// intensity, where 1 is the brightest character of the ramp
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Light {
+    /// a light infinitely far away, e.g. the sun. `direction` is the direction the light travels
+    /// in, it does not have to be normalised
+    Directional { direction: Vector, intensity: f32 },
+    /// a light emitting uniformly in all directions from `position`
+    Point { position: Vector, intensity: f32 },
+    /// light which reaches every surface equally, regardless of its orientation
+    Ambient { intensity: f32 },
+}
+
+impl Light {
+    /// the normalised direction from `point` towards the light and the distance to it, or `None`
+    /// for ambient light, which has no direction
+    pub fn direction_from(&self, point: &Vector) -> Option<(Vector, f32)> {
+        match self {
+            Light::Directional { direction, .. } => Some((-direction.normalise(), f32::INFINITY)),
+            Light::Point { position, .. } => {
+                let to_light = position - point;
+                Some((to_light.normalise(), to_light.norm()))
+            }
+            Light::Ambient { .. } => None,
+        }
+    }
+    pub fn intensity(&self) -> f32 {
+        match self {
+            Light::Directional { intensity, .. }
+            | Light::Point { intensity, .. }
+            | Light::Ambient { intensity } => *intensity,
+        }
+    }
+}
+
+/// the parameters of the Blinn-Phong specular highlight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Specular {
+    /// how bright the highlight is relative to the light's intensity
+    pub strength: f32,
+    /// the exponent of the highlight, higher values give smaller and sharper highlights
+    pub shininess: f32,
+}
+
+impl Default for Specular {
+    fn default() -> Specular {
+        Specular {
+            strength: 0.4,
+            shininess: 32.,
+        }
+    }
+}
+
+/// Lambertian diffuse term plus the optional Blinn-Phong specular term of a single light.
+/// `normal`, `to_light` and `to_viewer` should be normalised
+pub fn shade_single_light(
+    normal: &Vector,
+    to_light: &Vector,
+    to_viewer: &Vector,
+    specular: Option<&Specular>,
+) -> f32 {
+    let diffuse = normal.dot(to_light).max(0.);
+    if diffuse == 0. {
+        return 0.;
+    }
+    let highlight = specular.map_or(0., |specular| {
+        let halfway = (to_light + to_viewer).normalise();
+        specular.strength * normal.dot(&halfway).max(0.).powf(specular.shininess)
+    });
+    diffuse + highlight
+}
+
+/// how the light blocked by other objects, or other parts of the same object, is computed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shadows {
+    /// every light reaches every surface facing it
+    None,
+    /// a surface either sees a light or does not
+    Hard,
+    /// surfaces which the shadow ray passes close to are partially lit, giving a penumbra. Lower
+    /// `softness` values give wider penumbras
+    Soft { softness: f32 },
+}
+
+/// the fraction, between 0 and 1, of a light which reaches the surface at `hit` from
+/// `to_light`, found by marching a ray through the SDF of the object towards the light.
+/// `to_light` should be normalised
+pub fn shadow_factor(
+    object: &impl Object3D,
+    hit: &SurfaceHit,
+    to_light: &Vector,
+    light_distance: f32,
+    shadows: Shadows,
+) -> f32 {
+    let softness = match shadows {
+        Shadows::None => return 1.,
+        Shadows::Hard => None,
+        Shadows::Soft { softness } => Some(softness),
+    };
+    // the ray leaves from just above the surface, and measures its distances from there
+    let origin = hit.position + SHADOW_BIAS * hit.normal;
+    let max_distance = light_distance.min(MAX_SHADOW_DISTANCE);
+    let mut light_fraction: f32 = 1.;
+    let mut travelled = 0.;
+    for _ in 0..MAX_SHADOW_ITERATIONS {
+        let distance = object.signed_distance_function(&(origin + travelled * to_light));
+        if distance < SHADOW_HIT_DISTANCE {
+            return 0.;
+        }
+        if let Some(softness) = softness {
+            // the closer the ray passes to the surface, relative to how far it has travelled,
+            // the deeper in the penumbra the point is
+            light_fraction = light_fraction.min(softness * distance / travelled.max(SHADOW_BIAS));
+        }
+        travelled += distance;
+        if travelled > max_distance {
+            break;
+        }
+    }
+    light_fraction.clamp(0., 1.)
+}
+
+/// darkens the ambient light in creases and concave regions by sampling the SDF of the object at
+/// a few points along the surface normal. Where the surface is flat the SDF grows as fast as the
+/// samples move away from it; where other surfaces are nearby it grows slower
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmbientOcclusion {
+    pub samples: u8,
+    /// the distance between consecutive samples along the normal
+    pub step: f32,
+    /// how strongly the occlusion darkens the surface
+    pub strength: f32,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> AmbientOcclusion {
+        AmbientOcclusion {
+            samples: 5,
+            step: 0.15,
+            strength: 1.5,
+        }
+    }
+}
+
+impl AmbientOcclusion {
+    /// the fraction, between 0 and 1, of the ambient light which reaches the surface at `hit`
+    pub fn ambient_factor(&self, object: &impl Object3D, hit: &SurfaceHit) -> f32 {
+        let mut occlusion = 0.;
+        // samples further away from the surface contribute less
+        let mut weight = 1.;
+        for sample in 1..=self.samples {
+            let expected_distance = sample as f32 * self.step;
+            let distance =
+                object.signed_distance_function(&(hit.position + expected_distance * hit.normal));
+            occlusion += weight * (expected_distance - distance).max(0.) / expected_distance;
+            weight *= 0.5;
+        }
+        (1. - self.strength * occlusion / 2.).clamp(0., 1.)
+    }
+}
+
+#[cfg(test)]
+mod test_lighting {
+    use super::*;
+    #[test]
+    fn test_lambert() {
+        let normal = unit_z();
+        let to_viewer = unit_z();
+        assert_eq!(shade_single_light(&normal, &unit_z(), &to_viewer, None), 1.);
+        assert_eq!(
+            shade_single_light(&normal, &-unit_z(), &to_viewer, None),
+            0.
+        );
+        let specular = Specular::default();
+        assert_eq!(
+            shade_single_light(
+                &normal,
+                &vector!(0, 1, -0.1).normalise(),
+                &to_viewer,
+                Some(&specular)
+            ),
+            0.
+        );
+        let grazing = vector!(1, 0, 1).normalise();
+        let diffuse = shade_single_light(&normal, &grazing, &to_viewer, None);
+        assert!((diffuse - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+    #[test]
+    fn test_light_directions() {
+        let point = vector!(1, 2, 3);
+        let light = Light::Point {
+            position: vector!(1, 2, 7),
+            intensity: 1.,
+        };
+        let (to_light, distance) = light.direction_from(&point).unwrap();
+        assert!((to_light - unit_z()).norm() < 1e-6);
+        assert!((distance - 4.).abs() < 1e-6);
+        let sun = Light::Directional {
+            direction: vector!(0, 0, -2),
+            intensity: 1.,
+        };
+        let (to_light, distance) = sun.direction_from(&point).unwrap();
+        assert!((to_light - unit_z()).norm() < 1e-6);
+        assert_eq!(distance, f32::INFINITY);
+        assert_eq!(
+            Light::Ambient { intensity: 1. }.direction_from(&point),
+            None
+        );
+    }
+    #[test]
+    fn test_ambient_light_ignores_the_normal() {
+        let scene = Scene {
+            lights: vec![Light::Ambient { intensity: 0.3 }],
+            specular: Some(Specular::default()),
+            shadows: Shadows::Hard,
+            ambient_occlusion: None,
+        };
+        let plane = plane::Plane::new(vector!(0, 0, 0), unit_z());
+        for normal in [unit_z(), -unit_z(), vector!(1, 1, 0).normalise()] {
+            let hit = SurfaceHit {
+                position: vector!(0, 0, 0),
+                normal,
+                inside: false,
+            };
+            assert_eq!(scene.shade(&plane, &hit, &unit_z()), 0.3);
+        }
+    }
+    #[test]
+    fn test_highlight_peaks_on_the_half_vector() {
+        let to_light = vector!(1, 0, 1).normalise();
+        let to_viewer = vector!(-0.2, 0.3, 1).normalise();
+        let halfway = (to_light + to_viewer).normalise();
+        let specular = Specular {
+            strength: 1.,
+            shininess: 16.,
+        };
+        let highlight = |normal: Vector| {
+            shade_single_light(&normal, &to_light, &to_viewer, Some(&specular))
+                - shade_single_light(&normal, &to_light, &to_viewer, None)
+        };
+        assert!((highlight(halfway) - 1.).abs() < 1e-5);
+        for offset in [unit_x(), -unit_x(), unit_y(), -unit_y()] {
+            assert!(highlight((halfway + 0.1 * offset).normalise()) < highlight(halfway));
+        }
+    }
+    /// a plane at z = 0 with a small sphere hovering above the origin
+    fn sphere_above_plane() -> Union {
+        Union::from_objects(boxed_vec![
+            plane::Plane::new(vector!(0, 0, 0), unit_z()),
+            sphere::Sphere::new(vector!(0, 0, 2), 0.5)
+        ])
+    }
+    fn hit_on_plane(x: f32) -> SurfaceHit {
+        SurfaceHit {
+            position: vector!(x, 0, 0),
+            normal: unit_z(),
+            inside: false,
+        }
+    }
+    #[test]
+    fn test_shadow_of_a_sphere() {
+        let object = sphere_above_plane();
+        let below = hit_on_plane(0.);
+        let shadow = |hit: &SurfaceHit, shadows| {
+            shadow_factor(&object, hit, &unit_z(), f32::INFINITY, shadows)
+        };
+        assert_eq!(shadow(&below, Shadows::Hard), 0.);
+        assert_eq!(shadow(&below, Shadows::None), 1.);
+        assert_eq!(shadow(&below, Shadows::Soft { softness: 8. }), 0.);
+        assert_eq!(shadow(&hit_on_plane(5.), Shadows::Hard), 1.);
+        // beside the sphere the ray to the light passes close to it, in the penumbra
+        let beside = hit_on_plane(0.7);
+        assert_eq!(shadow(&beside, Shadows::Hard), 1.);
+        let mut previous = 0.;
+        for softness in [1., 2., 4., 8., 16.] {
+            let light_fraction = shadow(&beside, Shadows::Soft { softness });
+            assert!((0. ..=1.).contains(&light_fraction));
+            // lower softness values give a wider and darker penumbra
+            assert!(
+                light_fraction >= previous,
+                "{} at {}",
+                light_fraction,
+                softness
+            );
+            previous = light_fraction;
+        }
+        assert!(shadow(&beside, Shadows::Soft { softness: 2. }) < 0.5);
+        assert_eq!(shadow(&beside, Shadows::Soft { softness: 100. }), 1.);
+    }
+    #[test]
+    fn test_ambient_occlusion_darkens_concave_corners() {
+        // a floor with a wall standing on its edge at x = 1
+        let floor = cuboid::Cuboid::new(4., 4., 1.);
+        let mut wall = cuboid::Cuboid::new(1., 4., 4.);
+        *wall.get_center_mut() = vector!(1.5, 0, 1.5);
+        let object = Union::from_objects(boxed_vec![floor, wall]);
+        let on_floor = |x: f32| SurfaceHit {
+            position: vector!(x, 0, 0.5),
+            normal: unit_z(),
+            inside: false,
+        };
+        let ambient_occlusion = AmbientOcclusion::default();
+        let open = ambient_occlusion.ambient_factor(&object, &on_floor(-1.));
+        let corner = ambient_occlusion.ambient_factor(&object, &on_floor(0.9));
+        assert!((open - 1.).abs() < 1e-3, "{}", open);
+        assert!(
+            corner < open - 0.1,
+            "{} in the corner, {} in the open",
+            corner,
+            open
+        );
+        assert!((0. ..=1.).contains(&corner));
+        let strong = AmbientOcclusion {
+            strength: 100.,
+            ..AmbientOcclusion::default()
+        };
+        assert_eq!(strong.ambient_factor(&object, &on_floor(0.9)), 0.);
+        let negative = AmbientOcclusion {
+            strength: -100.,
+            ..AmbientOcclusion::default()
+        };
+        assert_eq!(negative.ambient_factor(&object, &on_floor(0.9)), 1.);
+    }
+}
@@ -1,125 +1,215 @@
-mod constants;
-mod math;
-mod objects;
-mod prelude;
-mod scene;
-mod terminal;
-
-use prelude::*;
+use spinner::asciicast::CastWriter;
+use spinner::camera_controller::{CameraController, FlyCamera, OrbitCamera};
+use spinner::cli::{self, Options};
+use spinner::gif::GifWriter;
+use spinner::input::{self, Command};
+use spinner::physics::Simulation;
+use spinner::prelude::*;
+use spinner::terminal;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::time;
 
-fn define_scene_cuboid() -> cuboid::Cuboid {
-    let cube = cuboid::Cuboid::new(1., 2., 3.);
-    cube
+/// frames per second, both of the terminal and of the simulation
+const FPS: u32 = 50;
+const PROPAGATION_ITERATIONS_PER_FRAME: u32 = 10000;
+
+fn define_scene_cuboid() -> painted::Painted<cuboid::Cuboid> {
+    painted::Painted::new(cuboid::Cuboid::new(1., 2., 3.), Rgb::new(1., 0.55, 0.2))
 }
 
-#[allow(non_snake_case)]
-pub trait SolidBody: OrientableMut + Orientable {
-    /// gets the moment of inertia of a body when its rotation matrix is the identity matrix. If it
-    /// is different, the new moment of inertia is `R*I*R^{-1}` where `I` is the moment returned by
-    /// this function
-    fn get_moment_of_inertia(&self) -> Matrix;
+fn define_simulation() -> Simulation<painted::Painted<cuboid::Cuboid>> {
+    Simulation::new(define_scene_cuboid(), vector!(0, 3, 0.01))
+}
 
-    fn get_current_moment_of_inertia(&self) -> Matrix {
-        let I_0 = self.get_moment_of_inertia();
-        let R_inv = self.get_inverse_orientation_matrix();
-        let R = R_inv.try_inverse().unwrap();
-        R * I_0 * R_inv
-    }
-    fn compute_energy_of_rotation(&self, angular_momentum: &Vector) -> f32 {
-        let I = self.get_current_moment_of_inertia();
-        (angular_momentum.transpose() * (I.try_inverse().unwrap()) * angular_momentum).trace()
-    }
-    fn propagate_rotation(
-        &mut self,
-        angular_momentum: &Vector,
-        energy: f32,
-        dt: f32,
-        index_for_gso: u8,
-    ) {
-        let I = self.get_current_moment_of_inertia();
-        let mut R = self.get_inverse_orientation_matrix().try_inverse().unwrap();
-        let mut omega = I.try_inverse().unwrap() * angular_momentum;
-        // make sure omega meets the energy requirement
-        let current_energy = (omega.transpose() * I * omega).trace();
-        omega /= (current_energy / energy).sqrt();
-        let R_dot = omega.cross_matrix() * R;
-        R += dt * R_dot;
-        // perform Gram Schmidt orthogonalization
-        R = gram_schmidt_orthogonalization(&R, index_for_gso);
+/// the camera the body is seen from when it is not steered from the keyboard
+fn default_camera(options: &Options) -> Camera {
+    OrbitCamera::new(vector!(0, 0, 0), 10.)
+        .camera()
+        .with_normals(options.normals)
+        .with_march(options.march)
+}
 
-        *(self.get_inverse_orientation_matrix_mut()) = R.try_inverse().unwrap();
-    }
+/// renders the body in its initial orientation to an image, without touching the terminal
+fn write_image(options: &Options, path: &Path) -> std::io::Result<()> {
+    let (width, height) = options.image_size.unwrap_or(cli::DEFAULT_IMAGE_SIZE);
+    let viewport = Viewport::new(width, height)
+        .with_h_w_ratio(1.)
+        .with_min_fov(MIN_FOV);
+    let presenter = ImagePresenter::default();
+    let frame = options.renderer.render(
+        &default_camera(options),
+        &Scene::default(),
+        &define_scene_cuboid(),
+        &presenter.frame_viewport(&viewport),
+    );
+    presenter.present(&frame).save(path)
 }
 
-impl SolidBody for cuboid::Cuboid {
-    #[inline]
-    fn get_moment_of_inertia(&self) -> Matrix {
-        let (a, b, c) = (self.side_a, self.side_b, self.side_c);
-        matrix_from_columns([
-            vector!(b * b + c * c, 0, 0),
-            vector!(0, a * a + c * c, 0),
-            vector!(0, 0, a * a + b * b),
-        ]) / 12.
+/// simulates the body for `--duration` seconds without touching the terminal, rendering every
+/// frame into the sample `viewport` and handing it to `record` with its time in simulated seconds
+fn record_simulation(
+    options: &Options,
+    viewport: &Viewport,
+    mut record: impl FnMut(f32, &FrameBuffer) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let (camera, scene) = (default_camera(options), Scene::default());
+    let mut simulation = define_simulation();
+    let duration = options.duration.unwrap_or(cli::DEFAULT_DURATION);
+    let n_frames = ((duration * FPS as f32).round() as u32).max(1);
+    for frame_num in 0..n_frames {
+        let frame = options
+            .renderer
+            .render(&camera, &scene, &simulation.body, viewport);
+        record(frame_num as f32 / FPS as f32, &frame)?;
+        simulation.advance(1. / FPS as f32, PROPAGATION_ITERATIONS_PER_FRAME);
     }
+    Ok(())
+}
+
+/// records the simulation to an asciicast file, with the timestamps in simulated time
+fn record_cast(options: &Options, path: &Path) -> std::io::Result<()> {
+    let (columns, rows) = options.cast_size.unwrap_or(cli::DEFAULT_CAST_SIZE);
+    let viewport = Viewport::new(columns, rows).with_min_fov(MIN_FOV);
+    let mut cast = CastWriter::new(BufWriter::new(std::fs::File::create(path)?), columns, rows)?;
+    record_simulation(
+        options,
+        &options.presenter.frame_viewport(&viewport),
+        |time, frame| {
+            let mut screen_buffer = options.presenter.present(frame);
+            terminal::draw_border(&mut screen_buffer);
+            cast.write_frame(time, &screen_buffer)
+        },
+    )?;
+    cast.flush()
+}
+
+/// records the simulation to an animated GIF, played back at the simulated frame rate
+fn record_gif(options: &Options, path: &Path) -> std::io::Result<()> {
+    let (width, height) = options.image_size.unwrap_or(cli::DEFAULT_IMAGE_SIZE);
+    let viewport = Viewport::new(width, height)
+        .with_h_w_ratio(1.)
+        .with_min_fov(MIN_FOV);
+    let presenter = ImagePresenter::default();
+    let mut gif = GifWriter::new(
+        BufWriter::new(std::fs::File::create(path)?),
+        width,
+        height,
+        FPS,
+    )?;
+    record_simulation(options, &presenter.frame_viewport(&viewport), |_, frame| {
+        gif.write_frame(&presenter.present(frame))
+    })?;
+    gif.finish()?;
+    Ok(())
 }
 
 fn main() -> std::io::Result<()> {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if let Some(path) = &options.image {
+        return write_image(&options, path);
+    }
+    if let Some(path) = &options.cast {
+        return record_cast(&options, path);
+    }
+    if let Some(path) = &options.gif {
+        return record_gif(&options, path);
+    }
     let mut stdout = std::io::stdout();
-    let mut camera = Camera::default();
-    camera.position = vector!(-1, -10, 0);
-    let mut screen_buffer = terminal::initialize_screen_buffer();
+    let mut orbit_camera = OrbitCamera::new(vector!(0, 0, 0), 10.);
+    let mut fly_camera: Option<FlyCamera> = None;
+    let scene = Scene::default();
+    let mut viewport = terminal::viewport_from_terminal()?;
     // define the scene to be rendered
-    let mut object = define_scene_cuboid();
-    let program_start = time::Instant::now();
+    let mut simulation = define_simulation();
+    let _interactive_terminal = terminal::InteractiveTerminal::enter(&mut stdout)?;
     terminal::clear_screen(&mut stdout)?;
-    let angular_momentum = vector!(0, 3, 0.01);
-    let energy = object.compute_energy_of_rotation(&angular_momentum);
-    let mut index_for_gso = 0;
-    let fps = 50;
-    let propagation_iterations_per_frame = 10000;
+    let mut screen_writer = terminal::ScreenWriter::new();
+    let mut paused = false;
     loop {
         let frame_start_time = time::Instant::now();
-
-        for _ in 0..propagation_iterations_per_frame {
-            object.propagate_rotation(
-                &angular_momentum,
-                energy,
-                1. / (fps * propagation_iterations_per_frame) as f32,
-                index_for_gso,
-            );
-            index_for_gso = (index_for_gso + 1) % 3;
+        let mut frames_to_propagate = if paused { 0 } else { 1 };
+        for command in input::poll_commands()? {
+            match command {
+                Command::Quit => return Ok(()),
+                Command::TogglePause => paused = !paused,
+                // while running the frame is propagated anyway
+                Command::Step if paused => frames_to_propagate += 1,
+                Command::Step => {}
+                Command::Reset => simulation.reset(),
+                Command::MoveCamera(motion) => match fly_camera.as_mut() {
+                    Some(fly_camera) => fly_camera.apply(motion),
+                    None => orbit_camera.apply(motion),
+                },
+                Command::ToggleCameraMode => {
+                    fly_camera = match fly_camera {
+                        Some(_) => None,
+                        None => Some(orbit_camera.to_fly_camera()),
+                    }
+                }
+                Command::Resize(columns, rows) => {
+                    viewport = terminal::viewport_for_terminal_size((columns, rows));
+                    terminal::clear_screen(&mut stdout)?;
+                    screen_writer.invalidate();
+                }
+            }
         }
 
-        // compute the light intensities for each pixel
-        for row in 1..HEIGHT - 1 {
-            for col in 1..WIDTH - 1 {
-                let cam_ray = camera.get_ray_from_camera(row, col);
-                let char_to_place = camera.compute_light_intensity(&object, &cam_ray);
+        for _ in 0..frames_to_propagate {
+            simulation.advance(1. / FPS as f32, PROPAGATION_ITERATIONS_PER_FRAME);
+        }
 
-                screen_buffer[row as usize][col as usize] = char_to_place;
-            }
+        let camera = match &fly_camera {
+            Some(fly_camera) => fly_camera.camera(),
+            None => orbit_camera.camera(),
         }
+        .with_normals(options.normals)
+        .with_march(options.march);
+        // compute the light intensities for each pixel
+        let frame = options.renderer.render(
+            &camera,
+            &scene,
+            &simulation.body,
+            &options.presenter.frame_viewport(&viewport),
+        );
+        let mut screen_buffer = options.presenter.present(&frame);
+        terminal::draw_border(&mut screen_buffer);
         let end_of_render = time::Instant::now();
 
-        // draw the computed ligth intensities to the screen
-        for (row_num, row) in screen_buffer.iter().enumerate() {
+        // draw the cells which changed since the last frame to the screen
+        screen_writer.draw(&mut stdout, &screen_buffer)?;
+        stdout.flush()?;
+        let end_of_draw = time::Instant::now();
+        // print some FPS statistics, line by line since raw mode does not return the carriage
+        let statistics = [
+            format!("FPS STATISTICS:{}", if paused { " (paused)" } else { "" }),
+            format!(
+                "    Time to render: {} ms",
+                end_of_render.duration_since(frame_start_time).as_millis()
+            ),
+            format!(
+                "    Time to draw: {} ms",
+                end_of_draw.duration_since(end_of_render).as_millis()
+            ),
+        ];
+        for (line_num, line) in statistics.iter().enumerate() {
             terminal::print_to_screen(
                 &mut stdout,
-                (row_num as u16, 0),
-                &(*row).iter().collect::<String>(),
+                ((viewport.height + line_num) as u16, 0),
+                &format!("{:<width$}", line, width = viewport.width),
             )?;
         }
-        // print some FPS statistics
-        terminal::print_to_screen(
-            &mut stdout,
-            (HEIGHT as u16, 0),
-            &format!(
-                "FPS STATISTICS:\n    Time to render: {} ms \n    Time to draw: {} ms",
-                end_of_render.duration_since(frame_start_time).as_millis(),
-                end_of_render.elapsed().as_millis()
-            ),
-        )?;
-        terminal::fps_cap(fps, &frame_start_time);
+        stdout.flush()?;
+        terminal::fps_cap(FPS, &frame_start_time);
     }
 }
@@ -15,12 +15,13 @@ impl Normalize for Vector {
     }
 }
 
+#[macro_export]
 macro_rules! vector {
     ($x: expr, $y:expr, $z: expr) => {{
-        Vector::from([$x as f32, $y as f32, $z as f32])
+        $crate::math::Vector::from([$x as f32, $y as f32, $z as f32])
     }};
 }
-pub(crate) use vector;
+pub use vector;
 
 pub type Matrix = nalgebra::SMatrix<f32, 3, 3>;
 
@@ -78,13 +79,15 @@ mod test_math {
     }
     #[test]
     fn test_normalize_trait() {
-        let difference = vector!(3, 4, 5).normalise() - vector!(0.42426, 0.56568, 0.7071);
+        let difference = vector!(3, 4, 5).normalise()
+            - vector!(0.42426, 0.56568, std::f32::consts::FRAC_1_SQRT_2);
         assert!(difference.dot(&difference) < 0.001)
     }
     #[test]
+    #[allow(non_snake_case)]
     fn test_matrix_from_col() {
         let A = super::matrix_from_columns([vector!(2, 0, 0), vector!(0, 2, 0), vector!(0, 0, 2)]);
-        assert!(A * A == 2 as f32 * A)
+        assert!(A * A == 2_f32 * A)
     }
     #[test]
     fn test_gso() {
@@ -0,0 +1,81 @@
+use crate::prelude::*;
+
+/// how the camera estimates the normal vector to the surface at the points its rays hit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormalEstimator {
+    /// central differences of the SDF along each axis, 6 extra SDF evaluations
+    CentralDifferences,
+    /// differences of the SDF at the corners of a tetrahedron around the hit point, 4 extra SDF
+    /// evaluations
+    Tetrahedral,
+    /// the exact gradient of the SDF for objects which provide one, tetrahedral otherwise
+    #[default]
+    Analytic,
+}
+
+/// the directions of the corners of the tetrahedron sampled by `NormalEstimator::Tetrahedral`
+const TETRAHEDRON: [(f32, f32, f32); 4] =
+    [(1., -1., -1.), (-1., -1., 1.), (-1., 1., -1.), (1., 1., 1.)];
+
+impl NormalEstimator {
+    /// the normalised normal vector to the surface of the object at `position`. `epsilon` is how
+    /// far from `position` the SDF is sampled by the numerical estimators
+    pub fn estimate(self, object: &impl Object3D, position: &Vector, epsilon: f32) -> Vector {
+        let sdf = |offset: Vector| object.signed_distance_function(&(position + epsilon * offset));
+        match self {
+            NormalEstimator::CentralDifferences => vector!(
+                sdf(unit_x()) - sdf(-unit_x()),
+                sdf(unit_y()) - sdf(-unit_y()),
+                sdf(unit_z()) - sdf(-unit_z())
+            )
+            .normalise(),
+            NormalEstimator::Tetrahedral => TETRAHEDRON
+                .iter()
+                .map(|&(x, y, z)| {
+                    let corner = vector!(x, y, z);
+                    corner * sdf(corner)
+                })
+                .sum::<Vector>()
+                .normalise(),
+            NormalEstimator::Analytic => match object.gradient(position) {
+                Some(gradient) => gradient.normalise(),
+                None => NormalEstimator::Tetrahedral.estimate(object, position, epsilon),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_normals {
+    use super::*;
+    #[test]
+    fn test_estimators_agree_on_a_sphere() {
+        let mut sphere = sphere::Sphere::new(vector!(1, 2, 3), 2.);
+        sphere.set_orientation_matrix(&gram_schmidt_orthogonalization(
+            &Matrix::new(1., 2., 0., 0., 1., 3., 1., 0., 1.),
+            0,
+        ));
+        let direction = vector!(1, -2, 0.5).normalise();
+        let position = vector!(1, 2, 3) + 2. * direction;
+        for estimator in [
+            NormalEstimator::CentralDifferences,
+            NormalEstimator::Tetrahedral,
+            NormalEstimator::Analytic,
+        ] {
+            let normal = estimator.estimate(&sphere, &position, 0.001);
+            assert!(
+                (normal - direction).norm() < 1e-2,
+                "{:?} estimated {}",
+                estimator,
+                normal
+            );
+        }
+    }
+    #[test]
+    fn test_sphere_has_no_gradient_at_its_centre() {
+        let sphere = sphere::Sphere::new(vector!(1, 2, 3), 2.);
+        assert_eq!(sphere.gradient(&vector!(1, 2, 3)), None);
+        let gradient = sphere.gradient(&vector!(1, 2, 5)).unwrap();
+        assert!((gradient - unit_z()).norm() < 1e-6);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::prelude::*;
+
+/// a sphere enclosing the whole surface of an object, so rays and SDF evaluations far away from
+/// it can be answered without looking at the object itself
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vector,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vector, radius: f32) -> BoundingSphere {
+        BoundingSphere { center, radius }
+    }
+    /// the signed distance from `point` to the surface of the sphere. Since the object is inside
+    /// the sphere, this is never more than the distance to the object
+    pub fn distance(&self, point: &Vector) -> f32 {
+        (point - self.center).norm() - self.radius
+    }
+    /// the smallest sphere enclosing both spheres
+    pub fn enclosing(&self, other: &BoundingSphere) -> BoundingSphere {
+        let offset = other.center - self.center;
+        let distance = offset.norm();
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+        let radius = (distance + self.radius + other.radius) / 2.;
+        BoundingSphere::new(
+            self.center + (radius - self.radius) / distance * offset,
+            radius,
+        )
+    }
+    /// a sphere enclosing all the given bounds, or `None` if one of them is unbounded
+    pub fn enclosing_all<'a>(
+        bounds: impl IntoIterator<Item = &'a Option<BoundingSphere>>,
+    ) -> Option<BoundingSphere> {
+        bounds.into_iter().try_fold(None, |enclosing, bounds| {
+            let bounds = (*bounds)?;
+            Some(Some(match enclosing {
+                Some(enclosing) => bounds.enclosing(&enclosing),
+                None => bounds,
+            }))
+        })?
+    }
+    /// the smallest of the given bounds, which encloses their intersection, or `None` if they
+    /// are all unbounded
+    pub fn smallest<'a>(
+        bounds: impl IntoIterator<Item = &'a Option<BoundingSphere>>,
+    ) -> Option<BoundingSphere> {
+        bounds
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| a.radius.partial_cmp(&b.radius).unwrap())
+            .copied()
+    }
+    pub fn expanded(&self, margin: f32) -> BoundingSphere {
+        BoundingSphere::new(self.center, self.radius + margin)
+    }
+    /// moves a sphere given in the centered coordinates of an object to the coordinates its SDF
+    /// is evaluated in. The radius grows with the largest stretch of the orientation matrix, and
+    /// `None` is returned if the orientation can not be inverted
+    pub fn to_parent_frame(
+        &self,
+        inverse_orientation_matrix: &Matrix,
+        center: &Vector,
+    ) -> Option<BoundingSphere> {
+        let orientation_matrix = inverse_orientation_matrix.try_inverse()?;
+        let stretch = orientation_matrix.singular_values().max();
+        Some(BoundingSphere::new(
+            orientation_matrix * self.center + center,
+            self.radius * stretch,
+        ))
+    }
+    /// the range of distances along the ray from `origin` in the normalised `direction` which are
+    /// inside the sphere, or `None` if the ray misses it. The range starts at 0 if the origin is
+    /// inside the sphere
+    pub fn ray_interval(&self, origin: &Vector, direction: &Vector) -> Option<(f32, f32)> {
+        let to_center = self.center - origin;
+        let along_ray = to_center.dot(direction);
+        let discriminant = along_ray.powi(2) - to_center.norm_squared() + self.radius.powi(2);
+        if discriminant < 0. {
+            return None;
+        }
+        let half_chord = discriminant.sqrt();
+        let exit = along_ray + half_chord;
+        if exit < 0. {
+            return None;
+        }
+        Some(((along_ray - half_chord).max(0.), exit))
+    }
+}
+
+#[cfg(test)]
+mod test_bounds {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// a sphere counting how often its SDF is evaluated
+    struct CountingSphere {
+        center: Vector,
+        radius: f32,
+        inverse_orientation_matrix: Matrix,
+        evaluations: Arc<AtomicUsize>,
+    }
+    impl SDF_Centered for CountingSphere {
+        fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
+            self.evaluations.fetch_add(1, Ordering::Relaxed);
+            position.norm() - self.radius
+        }
+        fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+            Some(BoundingSphere::new(vector!(0, 0, 0), self.radius))
+        }
+    }
+    impl Orientable for CountingSphere {
+        fn get_center(&self) -> &Vector {
+            &self.center
+        }
+        fn get_inverse_orientation_matrix(&self) -> &Matrix {
+            &self.inverse_orientation_matrix
+        }
+    }
+    impl OrientableMut for CountingSphere {
+        fn get_center_mut(&mut self) -> &mut Vector {
+            &mut self.center
+        }
+        fn get_inverse_orientation_matrix_mut(&mut self) -> &mut Matrix {
+            &mut self.inverse_orientation_matrix
+        }
+    }
+    #[test]
+    fn test_enclosing_contains_both_spheres() {
+        let a = BoundingSphere::new(vector!(-2, 0, 0), 1.);
+        let b = BoundingSphere::new(vector!(3, 1, 0), 2.);
+        let enclosing = a.enclosing(&b);
+        for sphere in [a, b] {
+            let farthest = (sphere.center - enclosing.center).norm() + sphere.radius;
+            assert!(farthest <= enclosing.radius + 1e-5);
+        }
+        let inner = BoundingSphere::new(vector!(3.5, 1, 0), 1.);
+        assert_eq!(b.enclosing(&inner), b);
+        assert_eq!(BoundingSphere::enclosing_all(&[Some(a), None]), None);
+    }
+    #[test]
+    fn test_far_children_are_skipped_without_changing_the_surface() {
+        let evaluations = Arc::new(AtomicUsize::new(0));
+        let union = Union::from_objects(boxed_vec![
+            sphere::Sphere::new(vector!(-3, 0, 0), 1.),
+            CountingSphere {
+                center: vector!(3, 0, 0),
+                radius: 1.,
+                inverse_orientation_matrix: Matrix::identity(),
+                evaluations: evaluations.clone(),
+            }
+        ]);
+        // on the first sphere, the bounds of the second are further away than its surface
+        assert_eq!(union.signed_distance_function(&vector!(-2, 0, 0)), 0.);
+        assert_eq!(evaluations.load(Ordering::Relaxed), 0);
+        union.signed_distance_function(&vector!(2.5, 0, 0));
+        assert_eq!(evaluations.load(Ordering::Relaxed), 1);
+        for point in [vector!(-2, 0, 0), vector!(3, 1, 0), vector!(0, 0, 0)] {
+            let exact = ((point - vector!(-3, 0, 0)).norm() - 1.)
+                .min((point - vector!(3, 0, 0)).norm() - 1.);
+            assert!((union.signed_distance_function(&point) - exact).abs() < 1e-5);
+        }
+        // far from the union, its SDF may underestimate the distance, but never overestimate it
+        let far_point = vector!(0, 40, 0);
+        let exact = (far_point - vector!(3, 0, 0)).norm() - 1.;
+        assert!(union.signed_distance_function(&far_point) <= exact);
+        let bounds = union.bounding_sphere().unwrap();
+        assert!(bounds
+            .ray_interval(&vector!(0, -10, 0), &vector!(0, 1, 0))
+            .is_some());
+        assert!(bounds
+            .ray_interval(&vector!(0, -10, 5), &vector!(0, 1, 0))
+            .is_none());
+    }
+}
@@ -40,4 +40,9 @@ impl CompoundObject for Cuboid {
     fn get_components_mut(&mut self) -> &mut dyn Object3D {
         &mut self.components
     }
+    /// the planes are unbounded, but the cuboid fits in the sphere through its corners
+    fn get_bounding_sphere(&self) -> Option<BoundingSphere> {
+        let half_diagonal = vector!(self.side_a, self.side_b, self.side_c).norm() / 2.;
+        Some(BoundingSphere::new(vector!(0, 0, 0), half_diagonal))
+    }
 }
@@ -6,6 +6,11 @@ pub mod pp;
 pub trait CompoundObject {
     fn get_components(&self) -> &dyn Object3D;
     fn get_components_mut(&mut self) -> &mut dyn Object3D;
+    /// the bounds of the object in the coordinates of its components' SDF. Override it when the
+    /// components are unbounded on their own, e.g. built from planes
+    fn get_bounding_sphere(&self) -> Option<BoundingSphere> {
+        self.get_components().bounding_sphere_centered()
+    }
 }
 
 impl<T> SDF_Centered for T
@@ -16,6 +21,18 @@ where
         self.get_components()
             .signed_distance_function_centered(position)
     }
+    fn color_centered(&self, position: &Vector) -> Option<Rgb> {
+        self.get_components().color_centered(position)
+    }
+    fn object_id_centered(&self, position: &Vector) -> Option<u32> {
+        self.get_components().object_id_centered(position)
+    }
+    fn gradient_centered(&self, position: &Vector) -> Option<Vector> {
+        self.get_components().gradient_centered(position)
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        self.get_bounding_sphere()
+    }
 }
 
 impl<T> Orientable for T
@@ -4,8 +4,8 @@ pub struct PP {
     components: SoftUnion,
 }
 
-impl PP {
-    pub fn default() -> PP {
+impl Default for PP {
+    fn default() -> PP {
         PP {
             components: SoftUnion::from_objects_default(boxed_vec![
                 sphere::Sphere::new(vector!(-1, 0, 0), 2.),
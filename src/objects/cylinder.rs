@@ -2,6 +2,8 @@ use crate::prelude::*;
 
 pub struct Cylinder {
     components: Intersection,
+    /// the infinite cylinder and the planes are unbounded, so the bounds are kept separately
+    bounding_sphere: BoundingSphere,
 }
 
 impl Cylinder {
@@ -12,6 +14,10 @@ impl Cylinder {
                 plane::Plane::new(base_center, -shaft_axis),
                 plane::Plane::new(base_center + height * shaft_axis, shaft_axis,)
             ]),
+            bounding_sphere: BoundingSphere::new(
+                base_center + height / 2. * shaft_axis,
+                (height / 2. * shaft_axis).norm().hypot(radius),
+            ),
         }
     }
 }
@@ -20,6 +26,18 @@ impl SDF_Centered for Cylinder {
     fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
         self.components.signed_distance_function_centered(position)
     }
+    fn color_centered(&self, position: &Vector) -> Option<Rgb> {
+        self.components.color_centered(position)
+    }
+    fn object_id_centered(&self, position: &Vector) -> Option<u32> {
+        self.components.object_id_centered(position)
+    }
+    fn gradient_centered(&self, position: &Vector) -> Option<Vector> {
+        self.components.gradient_centered(position)
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        Some(self.bounding_sphere)
+    }
 }
 
 impl Orientable for Cylinder {
@@ -1,9 +1,12 @@
+pub mod bounds;
 pub mod compound_objects;
 pub mod cylinder;
 pub mod infinite_cylinder;
 pub mod operations;
+pub mod painted;
 pub mod plane;
 pub mod sphere;
+pub mod tagged;
 
 use crate::prelude::*;
 
@@ -12,6 +15,26 @@ use crate::prelude::*;
 /// axis. This has to be implemented on basic objects (i.e. not compound ones)
 pub trait SDF_Centered {
     fn signed_distance_function_centered(&self, position: &Vector) -> f32;
+    /// the base colour of the surface closest to `position`, in the same coordinates as the SDF.
+    /// `None` means the object is unpainted and is drawn in the default colour
+    fn color_centered(&self, _position: &Vector) -> Option<Rgb> {
+        None
+    }
+    /// the id of the tagged object whose surface is closest to `position`, in the same
+    /// coordinates as the SDF. `None` means no object there was tagged
+    fn object_id_centered(&self, _position: &Vector) -> Option<u32> {
+        None
+    }
+    /// a sphere enclosing the whole surface, in the same coordinates as the SDF. `None` means the
+    /// object is unbounded, like a plane, or that no bounds are known
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        None
+    }
+    /// the exact gradient of the SDF at `position`, for objects where it is known in closed form.
+    /// `None` means it has to be estimated numerically
+    fn gradient_centered(&self, _position: &Vector) -> Option<Vector> {
+        None
+    }
 }
 
 pub trait Orientable {
@@ -36,13 +59,37 @@ pub trait Movable: OrientableMut {
 /// implement `Movable` for all structs that are `OrientableMut`
 impl<T> Movable for T where T: OrientableMut {}
 
-// All objects, both single and compound, should implement this trait
-pub trait Object3D: SDF_Centered + Orientable + OrientableMut {
+// All objects, both single and compound, should implement this trait. Objects have to be `Send`
+// and `Sync` so frames can be rendered from several threads
+pub trait Object3D: SDF_Centered + Orientable + OrientableMut + Send + Sync {
     fn signed_distance_function(&self, position: &Vector) -> f32 {
         self.signed_distance_function_centered(
             &(*(self.get_inverse_orientation_matrix()) * (position - *(self.get_center()))),
         )
     }
+    fn color(&self, position: &Vector) -> Option<Rgb> {
+        self.color_centered(
+            &(*(self.get_inverse_orientation_matrix()) * (position - *(self.get_center()))),
+        )
+    }
+    fn object_id(&self, position: &Vector) -> Option<u32> {
+        self.object_id_centered(
+            &(*(self.get_inverse_orientation_matrix()) * (position - *(self.get_center()))),
+        )
+    }
+    fn gradient(&self, position: &Vector) -> Option<Vector> {
+        let inverse_orientation_matrix = self.get_inverse_orientation_matrix();
+        let gradient = self.gradient_centered(
+            &(*inverse_orientation_matrix * (position - *(self.get_center()))),
+        )?;
+        // the chain rule through the change of coordinates
+        Some(inverse_orientation_matrix.transpose() * gradient)
+    }
+    /// a sphere enclosing the whole surface, in the same coordinates as `signed_distance_function`
+    fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        self.bounding_sphere_centered()?
+            .to_parent_frame(self.get_inverse_orientation_matrix(), self.get_center())
+    }
 }
 /// impl `Object3D` for every struct eligible
-impl<T> Object3D for T where T: SDF_Centered + Orientable + OrientableMut {}
+impl<T> Object3D for T where T: SDF_Centered + Orientable + OrientableMut + Send + Sync {}
@@ -1,15 +1,19 @@
+use super::utility_functions;
 use crate::prelude::*;
 
 pub struct Intersection {
     objects: Vec<Box<dyn Object3D>>,
+    bounding_sphere: Option<BoundingSphere>,
     center: Vector,
     inverse_orientation_matrix: Matrix,
 }
 
 impl Intersection {
     pub fn from_objects(objects: Vec<Box<dyn Object3D>>) -> Intersection {
+        let bounding_sphere = BoundingSphere::smallest(&utility_functions::child_bounds(&objects));
         Intersection {
             objects,
+            bounding_sphere,
             center: vector!(0, 0, 0),
             inverse_orientation_matrix: Matrix::identity(),
         }
@@ -41,6 +45,17 @@ impl SDF_Centered for Intersection {
             .max_by(|x, y| x.partial_cmp(y).unwrap())
             .unwrap()
     }
+    fn color_centered(&self, position: &Vector) -> Option<Rgb> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.color(position))
+    }
+    fn object_id_centered(&self, position: &Vector) -> Option<u32> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.object_id(position))
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        self.bounding_sphere
+    }
 }
 
 impl ObjectOperation for Intersection {
@@ -2,6 +2,9 @@ use super::utility_functions;
 use crate::prelude::*;
 pub struct SoftIntersection {
     objects: Vec<Box<dyn Object3D>>,
+    /// the bounds of each object, in the order of `objects`
+    child_bounds: Vec<Option<BoundingSphere>>,
+    bounding_sphere: Option<BoundingSphere>,
     center: Vector,
     inverse_orientation_matrix: Matrix,
     smu_epsilon: f32,
@@ -9,8 +12,12 @@ pub struct SoftIntersection {
 
 impl SoftIntersection {
     pub fn from_objects(objects: Vec<Box<dyn Object3D>>, smu_epsilon: f32) -> SoftIntersection {
+        let child_bounds = utility_functions::child_bounds(&objects);
+        let bounding_sphere = BoundingSphere::smallest(&child_bounds);
         SoftIntersection {
             objects,
+            child_bounds,
+            bounding_sphere,
             center: vector!(0, 0, 0),
             inverse_orientation_matrix: Matrix::identity(),
             smu_epsilon,
@@ -37,14 +44,29 @@ impl OrientableMut for SoftIntersection {
 
 impl SDF_Centered for SoftIntersection {
     fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
-        let (best_distance, second_best_distance) =
-            utility_functions::best_two_distances(&self.objects, position, false);
+        let (best_distance, second_best_distance) = utility_functions::best_two_distances(
+            &self.objects,
+            &self.child_bounds,
+            position,
+            false,
+        );
         utility_functions::smooth_maximum_unit(
             best_distance,
             second_best_distance,
             self.smu_epsilon,
         )
     }
+    fn color_centered(&self, position: &Vector) -> Option<Rgb> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.color(position))
+    }
+    fn object_id_centered(&self, position: &Vector) -> Option<u32> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.object_id(position))
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        self.bounding_sphere
+    }
 }
 
 impl ObjectOperation for SoftIntersection {
@@ -5,6 +5,9 @@ use crate::prelude::*;
 /// edge
 pub struct SoftUnion {
     objects: Vec<Box<dyn Object3D>>,
+    /// the bounds of each object, in the order of `objects`
+    child_bounds: Vec<Option<BoundingSphere>>,
+    bounding_sphere: Option<BoundingSphere>,
     center: Vector,
     inverse_orientation_matrix: Matrix,
     smu_epsilon: f32,
@@ -12,8 +15,14 @@ pub struct SoftUnion {
 
 impl SoftUnion {
     pub fn from_objects(objects: Vec<Box<dyn Object3D>>, smu_epsilon: f32) -> SoftUnion {
+        let child_bounds = utility_functions::child_bounds(&objects);
+        // the soft minimum bulges out of the union by at most half the square root of epsilon
+        let bounding_sphere = BoundingSphere::enclosing_all(&child_bounds)
+            .map(|bounds| bounds.expanded(smu_epsilon.sqrt() / 2.));
         SoftUnion {
             objects,
+            child_bounds,
+            bounding_sphere,
             center: vector!(0, 0, 0),
             inverse_orientation_matrix: Matrix::identity(),
             smu_epsilon,
@@ -41,14 +50,29 @@ impl OrientableMut for SoftUnion {
 impl SDF_Centered for SoftUnion {
     fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
         // find closest and second closest distances. If both are less than a set value, return the softmim of the two
-        let (best_distance, second_best_distance) =
-            utility_functions::best_two_distances(&self.objects, position, true);
+        let (best_distance, second_best_distance) = utility_functions::best_two_distances(
+            &self.objects,
+            &self.child_bounds,
+            position,
+            true,
+        );
         -utility_functions::smooth_maximum_unit(
             -best_distance,
             -second_best_distance,
             self.smu_epsilon,
         )
     }
+    fn color_centered(&self, position: &Vector) -> Option<Rgb> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.color(position))
+    }
+    fn object_id_centered(&self, position: &Vector) -> Option<u32> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.object_id(position))
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        self.bounding_sphere
+    }
 }
 impl ObjectOperation for SoftUnion {
     fn from_objects_default(objects: Vec<Box<dyn Object3D>>) -> SoftUnion {
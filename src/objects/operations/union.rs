@@ -1,15 +1,23 @@
+use super::utility_functions;
 use crate::prelude::*;
 
 pub struct Union {
     objects: Vec<Box<dyn Object3D>>,
+    /// the bounds of each object, in the order of `objects`
+    child_bounds: Vec<Option<BoundingSphere>>,
+    bounding_sphere: Option<BoundingSphere>,
     center: Vector,
     inverse_orientation_matrix: Matrix,
 }
 
 impl Union {
     pub fn from_objects(objects: Vec<Box<dyn Object3D>>) -> Union {
+        let child_bounds = utility_functions::child_bounds(&objects);
+        let bounding_sphere = BoundingSphere::enclosing_all(&child_bounds);
         Union {
             objects,
+            child_bounds,
+            bounding_sphere,
             center: vector!(0, 0, 0),
             inverse_orientation_matrix: Matrix::identity(),
         }
@@ -35,11 +43,25 @@ impl OrientableMut for Union {
 
 impl SDF_Centered for Union {
     fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
+        // objects whose bounds are further than the closest distance so far can not be closer
         self.objects
             .iter()
-            .map(|obj| obj.signed_distance_function(position))
-            .min_by(|x, y| x.partial_cmp(y).unwrap())
-            .unwrap()
+            .zip(&self.child_bounds)
+            .fold(f32::MAX, |closest, (obj, bounds)| {
+                utility_functions::bounded_distance(obj.as_ref(), bounds, position, closest)
+                    .map_or(closest, |distance| closest.min(distance))
+            })
+    }
+    fn color_centered(&self, position: &Vector) -> Option<Rgb> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.color(position))
+    }
+    fn object_id_centered(&self, position: &Vector) -> Option<u32> {
+        utility_functions::closest_object(&self.objects, position)
+            .and_then(|obj| obj.object_id(position))
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        self.bounding_sphere
     }
 }
 
@@ -7,39 +7,78 @@ pub fn smooth_maximum_unit(a: f32, b: f32, epsilon: f32) -> f32 {
 
 // finds either the two highest or smallest distances from the position to each of the objects. If
 // looking_for_min it returns the smallest and second_smallest, if not, the largest and second
-// largest
+// largest. When looking_for_min, objects whose bounds are further than the second smallest distance
+// so far are skipped, since they cannot be one of the two closest
 pub fn best_two_distances(
-    objects: &Vec<Box<dyn Object3D>>,
+    objects: &[Box<dyn Object3D>],
+    bounds: &[Option<BoundingSphere>],
     position: &Vector,
     looking_for_min: bool,
 ) -> (f32, f32) {
-    let (best_distance, second_best_distance) = objects
-        .iter()
-        .map(|obj| {
-            if looking_for_min {
-                obj.signed_distance_function(position)
+    let (best_distance, second_best_distance) = objects.iter().zip(bounds).fold(
+        (f32::MAX, f32::MAX),
+        |(best, second_best), (obj, bounds)| {
+            let skip_from = if looking_for_min {
+                second_best
             } else {
-                -obj.signed_distance_function(position)
-            }
-        })
-        .fold(
-            (f32::MAX, f32::MAX),
-            |(best, second_best), current_distance| {
-                let mut best_local = best;
-                let mut second_best_local = second_best;
+                f32::INFINITY
+            };
+            let Some(distance) = bounded_distance(obj.as_ref(), bounds, position, skip_from) else {
+                return (best, second_best);
+            };
+            let current_distance = if looking_for_min { distance } else { -distance };
+            let mut best_local = best;
+            let mut second_best_local = second_best;
 
-                if current_distance < best_local {
-                    second_best_local = best_local;
-                    best_local = current_distance;
-                } else if current_distance < second_best_local {
-                    second_best_local = current_distance;
-                }
-                (best_local, second_best_local)
-            },
-        );
+            if current_distance < best_local {
+                second_best_local = best_local;
+                best_local = current_distance;
+            } else if current_distance < second_best_local {
+                second_best_local = current_distance;
+            }
+            (best_local, second_best_local)
+        },
+    );
     if looking_for_min {
-        return (best_distance, second_best_distance);
+        (best_distance, second_best_distance)
     } else {
         (-best_distance, -second_best_distance)
     }
 }
+
+/// the object whose surface is closest to the position
+pub fn closest_object<'a>(
+    objects: &'a [Box<dyn Object3D>],
+    position: &Vector,
+) -> Option<&'a dyn Object3D> {
+    objects
+        .iter()
+        .map(|obj| (obj.signed_distance_function(position).abs(), obj))
+        .min_by(|(x, _), (y, _)| x.partial_cmp(y).unwrap())
+        .map(|(_, obj)| obj.as_ref())
+}
+
+/// the bounds of each object, in the coordinates of the operation holding them
+pub fn child_bounds(objects: &[Box<dyn Object3D>]) -> Vec<Option<BoundingSphere>> {
+    objects.iter().map(|obj| obj.bounding_sphere()).collect()
+}
+
+/// the SDF of the object, raised to the distance to its bounds where that is larger. Both are
+/// lower bounds of the distance to the object, so the result is too. Objects whose bounds are at
+/// least `skip_from` away are not evaluated and `None` is returned, since their result could not
+/// be lower than that
+pub fn bounded_distance(
+    obj: &dyn Object3D,
+    bounds: &Option<BoundingSphere>,
+    position: &Vector,
+    skip_from: f32,
+) -> Option<f32> {
+    match bounds {
+        Some(bounds) => {
+            let bounds_distance = bounds.distance(position);
+            (bounds_distance < skip_from)
+                .then(|| obj.signed_distance_function(position).max(bounds_distance))
+        }
+        None => Some(obj.signed_distance_function(position)),
+    }
+}
@@ -0,0 +1,49 @@
+use crate::prelude::*;
+
+/// gives an object a base colour, which the colour output modes shade. Objects which are not
+/// painted are drawn in white
+pub struct Painted<T: Object3D> {
+    pub object: T,
+    pub color: Rgb,
+}
+
+impl<T: Object3D> Painted<T> {
+    pub fn new(object: T, color: Rgb) -> Painted<T> {
+        Painted { object, color }
+    }
+}
+
+impl<T: Object3D> SDF_Centered for Painted<T> {
+    fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
+        self.object.signed_distance_function_centered(position)
+    }
+    fn color_centered(&self, _position: &Vector) -> Option<Rgb> {
+        Some(self.color)
+    }
+    fn object_id_centered(&self, position: &Vector) -> Option<u32> {
+        self.object.object_id_centered(position)
+    }
+    fn gradient_centered(&self, position: &Vector) -> Option<Vector> {
+        self.object.gradient_centered(position)
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        self.object.bounding_sphere_centered()
+    }
+}
+
+impl<T: Object3D> Orientable for Painted<T> {
+    fn get_center(&self) -> &Vector {
+        self.object.get_center()
+    }
+    fn get_inverse_orientation_matrix(&self) -> &Matrix {
+        self.object.get_inverse_orientation_matrix()
+    }
+}
+impl<T: Object3D> OrientableMut for Painted<T> {
+    fn get_center_mut(&mut self) -> &mut Vector {
+        self.object.get_center_mut()
+    }
+    fn get_inverse_orientation_matrix_mut(&mut self) -> &mut Matrix {
+        self.object.get_inverse_orientation_matrix_mut()
+    }
+}
@@ -24,6 +24,9 @@ impl SDF_Centered for Plane {
     fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
         position.dot(&self.n)
     }
+    fn gradient_centered(&self, _position: &Vector) -> Option<Vector> {
+        Some(self.n)
+    }
 }
 
 impl OrientableMut for Plane {
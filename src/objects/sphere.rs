@@ -22,6 +22,13 @@ impl SDF_Centered for Sphere {
     fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
         (*position).norm() - self.radius
     }
+    /// the direction from the centre, which is undefined at the centre itself
+    fn gradient_centered(&self, position: &Vector) -> Option<Vector> {
+        (position.norm() > 1e-6).then(|| position.normalise())
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        Some(BoundingSphere::new(vector!(0, 0, 0), self.radius))
+    }
 }
 impl Orientable for Sphere {
     fn get_center(&self) -> &Vector {
@@ -0,0 +1,49 @@
+use crate::prelude::*;
+
+/// gives an object an id, which the renderer records for every pixel showing it so presenters
+/// can tell objects apart
+pub struct Tagged<T: Object3D> {
+    pub object: T,
+    pub id: u32,
+}
+
+impl<T: Object3D> Tagged<T> {
+    pub fn new(object: T, id: u32) -> Tagged<T> {
+        Tagged { object, id }
+    }
+}
+
+impl<T: Object3D> SDF_Centered for Tagged<T> {
+    fn signed_distance_function_centered(&self, position: &Vector) -> f32 {
+        self.object.signed_distance_function_centered(position)
+    }
+    fn color_centered(&self, position: &Vector) -> Option<Rgb> {
+        self.object.color_centered(position)
+    }
+    fn object_id_centered(&self, _position: &Vector) -> Option<u32> {
+        Some(self.id)
+    }
+    fn gradient_centered(&self, position: &Vector) -> Option<Vector> {
+        self.object.gradient_centered(position)
+    }
+    fn bounding_sphere_centered(&self) -> Option<BoundingSphere> {
+        self.object.bounding_sphere_centered()
+    }
+}
+
+impl<T: Object3D> Orientable for Tagged<T> {
+    fn get_center(&self) -> &Vector {
+        self.object.get_center()
+    }
+    fn get_inverse_orientation_matrix(&self) -> &Matrix {
+        self.object.get_inverse_orientation_matrix()
+    }
+}
+impl<T: Object3D> OrientableMut for Tagged<T> {
+    fn get_center_mut(&mut self) -> &mut Vector {
+        self.object.get_center_mut()
+    }
+    fn get_inverse_orientation_matrix_mut(&mut self) -> &mut Matrix {
+        self.object.get_inverse_orientation_matrix_mut()
+    }
+}
@@ -0,0 +1,108 @@
+use crate::prelude::*;
+use crate::terminal;
+
+/// renders frames to memory instead of the terminal, for tests, logs and other programs. The
+/// viewports are in cells, like the terminal's
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OffscreenRenderer {
+    pub renderer: Renderer,
+    pub presenter: CellPresenter,
+}
+
+impl OffscreenRenderer {
+    pub fn new(renderer: Renderer, presenter: CellPresenter) -> OffscreenRenderer {
+        OffscreenRenderer {
+            renderer,
+            presenter,
+        }
+    }
+    /// renders the object into a screen buffer the size of the viewport
+    pub fn render(
+        &self,
+        camera: &Camera,
+        scene: &Scene,
+        object: &impl Object3D,
+        viewport: &Viewport,
+    ) -> ScreenBuffer {
+        let frame = self.renderer.render(
+            camera,
+            scene,
+            object,
+            &self.presenter.frame_viewport(viewport),
+        );
+        self.presenter.present(&frame)
+    }
+    /// the glyphs of every row of the frame, without colours
+    pub fn render_lines(
+        &self,
+        camera: &Camera,
+        scene: &Scene,
+        object: &impl Object3D,
+        viewport: &Viewport,
+    ) -> Vec<String> {
+        self.render(camera, scene, object, viewport).lines()
+    }
+    /// the glyphs of the frame, one line per row, without colours
+    pub fn render_to_string(
+        &self,
+        camera: &Camera,
+        scene: &Scene,
+        object: &impl Object3D,
+        viewport: &Viewport,
+    ) -> String {
+        self.render(camera, scene, object, viewport).to_string()
+    }
+    /// the frame with the ANSI escape codes of its colours, one line per row, for terminals and
+    /// pagers which understand them
+    pub fn render_to_ansi(
+        &self,
+        camera: &Camera,
+        scene: &Scene,
+        object: &impl Object3D,
+        viewport: &Viewport,
+    ) -> String {
+        let screen_buffer = self.render(camera, scene, object, viewport);
+        let mut out = vec![];
+        for (row_num, row) in screen_buffer.rows().enumerate() {
+            if row_num > 0 {
+                out.push(b'\n');
+            }
+            terminal::write_cells(&mut out, row).expect("writing to memory can not fail");
+        }
+        String::from_utf8(out).expect("the cells and escape codes are valid UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod test_offscreen {
+    use super::*;
+    #[test]
+    fn test_render_sphere_to_lines() {
+        let offscreen = OffscreenRenderer::default();
+        let (camera, scene) = (Camera::default(), Scene::default());
+        let sphere = sphere::Sphere::new(vector!(0, 0, 0), 2.);
+        let viewport = Viewport::new(21, 9);
+        let lines = offscreen.render_lines(&camera, &scene, &sphere, &viewport);
+        assert_eq!(lines.len(), 9);
+        assert!(lines.iter().all(|line| line.chars().count() == 21));
+        assert_ne!(lines[4].chars().nth(10), Some(' '));
+        assert_eq!(lines[0].chars().next(), Some(' '));
+        assert_eq!(
+            offscreen.render_to_string(&camera, &scene, &sphere, &viewport),
+            lines.join("\n")
+        );
+        let colored = OffscreenRenderer::new(
+            Renderer::default(),
+            CellPresenter {
+                color_output: ColorOutput {
+                    mode: ColorMode::TrueColor,
+                    ..ColorOutput::default()
+                },
+                ..CellPresenter::default()
+            },
+        );
+        let ansi = colored.render_to_ansi(&camera, &scene, &sphere, &viewport);
+        assert!(ansi.contains("\x1b["));
+        assert_eq!(ansi.lines().count(), 9);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::prelude::*;
+
+/// a rigid body whose rotation can be propagated in time from its angular momentum
+#[allow(non_snake_case)]
+pub trait SolidBody: OrientableMut + Orientable {
+    /// gets the moment of inertia of a body when its rotation matrix is the identity matrix. If it
+    /// is different, the new moment of inertia is `R*I*R^{-1}` where `I` is the moment returned by
+    /// this function
+    fn get_moment_of_inertia(&self) -> Matrix;
+
+    fn get_current_moment_of_inertia(&self) -> Matrix {
+        let I_0 = self.get_moment_of_inertia();
+        let R_inv = self.get_inverse_orientation_matrix();
+        let R = R_inv.try_inverse().unwrap();
+        R * I_0 * R_inv
+    }
+    fn compute_energy_of_rotation(&self, angular_momentum: &Vector) -> f32 {
+        let I = self.get_current_moment_of_inertia();
+        (angular_momentum.transpose() * (I.try_inverse().unwrap()) * angular_momentum).trace()
+    }
+    fn propagate_rotation(
+        &mut self,
+        angular_momentum: &Vector,
+        energy: f32,
+        dt: f32,
+        index_for_gso: u8,
+    ) {
+        let I = self.get_current_moment_of_inertia();
+        let mut R = self.get_inverse_orientation_matrix().try_inverse().unwrap();
+        let mut omega = I.try_inverse().unwrap() * angular_momentum;
+        // make sure omega meets the energy requirement
+        let current_energy = (omega.transpose() * I * omega).trace();
+        omega /= (current_energy / energy).sqrt();
+        let R_dot = omega.cross_matrix() * R;
+        R += dt * R_dot;
+        // perform Gram Schmidt orthogonalization
+        R = gram_schmidt_orthogonalization(&R, index_for_gso);
+
+        *(self.get_inverse_orientation_matrix_mut()) = R.try_inverse().unwrap();
+    }
+}
+
+impl SolidBody for cuboid::Cuboid {
+    #[inline]
+    fn get_moment_of_inertia(&self) -> Matrix {
+        let (a, b, c) = (self.side_a, self.side_b, self.side_c);
+        matrix_from_columns([
+            vector!(b * b + c * c, 0, 0),
+            vector!(0, a * a + c * c, 0),
+            vector!(0, 0, a * a + b * b),
+        ]) / 12.
+    }
+}
+
+impl<T: SolidBody + Object3D> SolidBody for painted::Painted<T> {
+    fn get_moment_of_inertia(&self) -> Matrix {
+        self.object.get_moment_of_inertia()
+    }
+}
+
+impl<T: SolidBody + Object3D> SolidBody for tagged::Tagged<T> {
+    fn get_moment_of_inertia(&self) -> Matrix {
+        self.object.get_moment_of_inertia()
+    }
+}
+
+/// a body spinning freely with a constant angular momentum, together with the state needed to
+/// propagate its rotation and to bring it back to where it started
+pub struct Simulation<T: SolidBody> {
+    pub body: T,
+    angular_momentum: Vector,
+    energy: f32,
+    initial_inverse_orientation: Matrix,
+    index_for_gso: u8,
+}
+
+impl<T: SolidBody> Simulation<T> {
+    pub fn new(body: T, angular_momentum: Vector) -> Simulation<T> {
+        let energy = body.compute_energy_of_rotation(&angular_momentum);
+        let initial_inverse_orientation = *body.get_inverse_orientation_matrix();
+        Simulation {
+            body,
+            angular_momentum,
+            energy,
+            initial_inverse_orientation,
+            index_for_gso: 0,
+        }
+    }
+    /// propagates the rotation of the body by `duration` seconds, split into `steps` equal
+    /// integration steps
+    pub fn advance(&mut self, duration: f32, steps: u32) {
+        let dt = duration / steps as f32;
+        for _ in 0..steps {
+            self.body.propagate_rotation(
+                &self.angular_momentum,
+                self.energy,
+                dt,
+                self.index_for_gso,
+            );
+            self.index_for_gso = (self.index_for_gso + 1) % 3;
+        }
+    }
+    /// puts the body back into the orientation it had when the simulation was created
+    pub fn reset(&mut self) {
+        *self.body.get_inverse_orientation_matrix_mut() = self.initial_inverse_orientation;
+        self.index_for_gso = 0;
+    }
+}
+
+#[cfg(test)]
+mod test_physics {
+    use super::*;
+
+    fn spinning_cuboid() -> Simulation<cuboid::Cuboid> {
+        Simulation::new(cuboid::Cuboid::new(1., 2., 3.), vector!(0, 3, 0.01))
+    }
+    #[test]
+    fn test_reset_restores_the_initial_state() {
+        let mut simulation = spinning_cuboid();
+        let initial_orientation = *simulation.body.get_inverse_orientation_matrix();
+        let angular_velocity = |simulation: &Simulation<cuboid::Cuboid>| {
+            simulation
+                .body
+                .get_current_moment_of_inertia()
+                .try_inverse()
+                .unwrap()
+                * simulation.angular_momentum
+        };
+        let initial_angular_velocity = angular_velocity(&simulation);
+        simulation.advance(0.7, 1000);
+        let orientation = *simulation.body.get_inverse_orientation_matrix();
+        assert!((orientation - initial_orientation).norm() > 0.1);
+        // the rotation stays a rotation and keeps its energy
+        assert!((orientation.transpose() * orientation - Matrix::identity()).norm() < 1e-3);
+        let energy = simulation
+            .body
+            .compute_energy_of_rotation(&simulation.angular_momentum);
+        assert!((energy - simulation.energy).abs() < 1e-2 * simulation.energy);
+
+        simulation.reset();
+        assert_eq!(
+            *simulation.body.get_inverse_orientation_matrix(),
+            initial_orientation
+        );
+        assert_eq!(simulation.angular_momentum, vector!(0, 3, 0.01));
+        assert!((angular_velocity(&simulation) - initial_angular_velocity).norm() < 1e-5);
+        // and the simulation goes on exactly like a fresh one
+        let mut fresh = spinning_cuboid();
+        simulation.advance(0.1, 100);
+        fresh.advance(0.1, 100);
+        assert_eq!(
+            simulation.body.get_inverse_orientation_matrix(),
+            fresh.body.get_inverse_orientation_matrix()
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! a self-contained PNG encoder for 8 bit RGB images. The pixel data is stored in uncompressed
+//! deflate blocks, which every decoder reads, so no compression library is needed
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// the most bytes a stored deflate block can hold
+const MAX_STORED_BLOCK: usize = 0xffff;
+/// PNG colour type of 8 bit RGB pixels
+const COLOR_TYPE_RGB: u8 = 2;
+
+/// the CRC-32 checksum used by PNG chunks, with the reversed polynomial 0xedb88320
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// the Adler-32 checksum which ends a zlib stream
+pub fn adler32(bytes: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % MODULUS;
+        b = (b + a) % MODULUS;
+    }
+    (b << 16) | a
+}
+
+/// wraps `data` in a zlib stream of stored deflate blocks
+pub fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // deflate with a 32K window, no preset dictionary and the fastest compression level, which
+    // makes the header a multiple of 31
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        // an empty stream still needs its final block
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let length = block.len() as u16;
+        stream.push(is_final as u8);
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// encodes an image given as rows of RGB bytes, from the top, as a PNG file
+pub fn encode_rgb(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    assert_eq!(
+        rgb.len(),
+        width * height * 3,
+        "the pixel data does not match the size of the image"
+    );
+    let mut header = vec![];
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // bit depth, colour type, compression, filter and interlace methods
+    header.extend_from_slice(&[8, COLOR_TYPE_RGB, 0, 0, 0]);
+    // every scanline starts with its filter type, 0 for none
+    let mut scanlines = Vec::with_capacity(rgb.len() + height);
+    // rows are sliced by hand since `chunks` panics on the empty rows of a zero width image
+    for row in 0..height {
+        scanlines.push(0);
+        scanlines.extend_from_slice(&rgb[row * width * 3..(row + 1) * width * 3]);
+    }
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+#[cfg(test)]
+mod test_png {
+    use super::*;
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+    /// the kind and data of every chunk of the PNG, checking their CRCs
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], &[u8])> {
+        assert_eq!(png[..8], SIGNATURE);
+        let mut chunks = vec![];
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind_and_data, crc) = (&rest[4..8 + length], &rest[8 + length..12 + length]);
+            assert_eq!(crc32(kind_and_data).to_be_bytes(), crc);
+            chunks.push((kind_and_data[..4].try_into().unwrap(), &kind_and_data[4..]));
+            rest = &rest[12 + length..];
+        }
+        chunks
+    }
+    #[test]
+    fn test_chunks_and_stored_lengths() {
+        for (width, height) in [(7, 5), (300, 80), (0, 3), (0, 0)] {
+            let rgb: Vec<u8> = (0..width * height * 3).map(|i| (i % 256) as u8).collect();
+            let png = encode_rgb(width, height, &rgb);
+            let chunks = chunks(&png);
+            let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+            assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+            assert_eq!(
+                chunks[0].1[..8],
+                [(width as u32).to_be_bytes(), (height as u32).to_be_bytes()].concat()
+            );
+            // walk the stored blocks of the zlib stream
+            let idat = chunks[1].1;
+            let (mut position, mut stored) = (2, 0);
+            loop {
+                let is_final = idat[position] == 1;
+                let length = u16::from_le_bytes([idat[position + 1], idat[position + 2]]);
+                let complement = u16::from_le_bytes([idat[position + 3], idat[position + 4]]);
+                assert_eq!(complement, !length);
+                stored += length as usize;
+                position += 5 + length as usize;
+                if is_final {
+                    break;
+                }
+            }
+            assert_eq!(stored, width * height * 3 + height);
+            assert_eq!(position + 4, idat.len());
+        }
+    }
+    #[test]
+    fn test_stored_blocks() {
+        let data: Vec<u8> = (0..70000_u32).map(|i| (i % 251) as u8).collect();
+        let stream = zlib_stored(&data);
+        // the header, two blocks with their 5 byte headers, and the checksum
+        assert_eq!(
+            stream.len(),
+            2 + 5 + MAX_STORED_BLOCK + 5 + 70000 - MAX_STORED_BLOCK + 4
+        );
+        assert_eq!(u16::from_be_bytes([stream[0], stream[1]]) % 31, 0);
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + MAX_STORED_BLOCK], 1);
+    }
+}
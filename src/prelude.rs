@@ -1,6 +1,12 @@
+pub use crate::color::*;
 pub use crate::constants::*;
+pub use crate::dither::Dithering;
+pub use crate::framebuffer::FrameBuffer;
+pub use crate::lighting::*;
 pub use crate::math::*;
+pub use crate::normals::NormalEstimator;
 pub use crate::objects::{
+    bounds::BoundingSphere,
     compound_objects::*,
     operations::{
         intersection::Intersection, soft_intersection::SoftIntersection, soft_union::SoftUnion,
@@ -8,14 +14,25 @@ pub use crate::objects::{
     },
     *,
 };
+pub use crate::offscreen::OffscreenRenderer;
+pub use crate::presenter::{
+    cells::*,
+    image::{Image, ImagePresenter},
+    Presenter,
+};
+pub use crate::ramp::CharRamp;
+pub use crate::renderer::Renderer;
 pub use crate::scene::*;
+pub use crate::supersampling::Supersampling;
+pub use crate::viewport::*;
 
+#[macro_export]
 macro_rules! boxed_vec {
     [$($object:expr),+] => {
         vec![$(Box::new($object)),+]
     }
 }
-pub(crate) use boxed_vec;
+pub use boxed_vec;
 
 pub fn unit_x() -> Vector {
     vector!(1, 0, 0)
@@ -0,0 +1,291 @@
+use crate::prelude::*;
+
+/// the shading of a sample, or `None` where the ray missed, together with its quantized level
+type QuantizedSample = (Option<Shading>, Option<f32>);
+
+const UPPER_HALF_BLOCK: char = '▀';
+const LOWER_HALF_BLOCK: char = '▄';
+const FULL_BLOCK: char = '█';
+const BRAILLE_BLANK: u32 = 0x2800;
+/// the bit of each braille dot, indexed by [row][column] of the 2x4 dot grid
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// how the samples of a frame are packed into terminal cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    /// one sample per cell, drawn with a character of matching brightness
+    Ascii,
+    /// two vertical samples per cell, drawn with the upper and lower half block characters
+    HalfBlock,
+    /// 2x4 samples per cell, drawn with the dots of braille characters
+    Braille,
+}
+
+impl RenderMode {
+    /// the number of samples per cell as (columns, rows)
+    pub fn samples_per_cell(self) -> (usize, usize) {
+        match self {
+            RenderMode::Ascii => (1, 1),
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+    /// the viewport of the individual samples: it covers the same field of view as the cell
+    /// viewport, with every cell split into `samples_per_cell` samples
+    pub fn sample_viewport(self, viewport: &Viewport) -> Viewport {
+        let (columns, rows) = self.samples_per_cell();
+        Viewport {
+            width: viewport.width * columns,
+            height: viewport.height * rows,
+            fov: viewport.fov,
+            h_w_ratio: viewport.h_w_ratio * columns as f32 / rows as f32,
+        }
+    }
+}
+
+/// turns the frame buffer into terminal cells, according to a render mode and a colour output
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellPresenter {
+    pub mode: RenderMode,
+    pub color_output: ColorOutput,
+    /// the characters used for the brightness in `RenderMode::Ascii`
+    pub ramp: CharRamp,
+    pub dithering: Dithering,
+}
+
+impl Presenter for CellPresenter {
+    type Output = ScreenBuffer;
+
+    fn frame_viewport(&self, viewport: &Viewport) -> Viewport {
+        self.mode.sample_viewport(viewport)
+    }
+    fn present(&self, frame: &FrameBuffer) -> ScreenBuffer {
+        let (columns, rows) = self.mode.samples_per_cell();
+        let (width, height) = (frame.width() / columns, frame.height() / rows);
+        let mut screen_buffer = ScreenBuffer::new(width, height);
+        let samples = frame.pixels();
+        let levels = self.quantize(samples, frame.width());
+        for row in 0..height {
+            for col in 0..width {
+                // the sample at (`sub_row`, `sub_col`) within the cell and its quantized level
+                let sample = |sub_row: usize, sub_col: usize| {
+                    let index = (row * rows + sub_row) * frame.width() + col * columns + sub_col;
+                    (samples[index], levels[index])
+                };
+                let cell = match self.mode {
+                    RenderMode::Ascii => self.ascii_cell(sample(0, 0)),
+                    RenderMode::HalfBlock => self.half_block_cell(sample(0, 0), sample(1, 0)),
+                    RenderMode::Braille => {
+                        let mut cell_samples = [[(None, None); 2]; 4];
+                        for (sub_row, samples_row) in cell_samples.iter_mut().enumerate() {
+                            for (sub_col, value) in samples_row.iter_mut().enumerate() {
+                                *value = sample(sub_row, sub_col);
+                            }
+                        }
+                        self.braille_cell(&cell_samples)
+                    }
+                };
+                screen_buffer.set(row, col, cell);
+            }
+        }
+        screen_buffer
+    }
+}
+
+impl CellPresenter {
+    /// the levels the output can show for the samples, dithered if requested. In
+    /// `RenderMode::Ascii` these are the levels of the glyphs of the ramp, otherwise samples are
+    /// either switched off (0) or on (1)
+    fn quantize(&self, samples: &[Option<Shading>], width: usize) -> Vec<Option<f32>> {
+        let mut levels: Vec<Option<f32>> = samples
+            .iter()
+            .map(|sample| sample.map(|shading| self.ramp.adjust(shading.intensity)))
+            .collect();
+        match self.mode {
+            RenderMode::Ascii => self
+                .dithering
+                .quantize(&mut levels, width, self.ramp.levels()),
+            _ => self.dithering.quantize(&mut levels, width, &[0., 1.]),
+        }
+        levels
+    }
+
+    /// whether a sample is drawn at all. With colours the brightness is carried by the colour,
+    /// so every sample hitting the object is drawn, without them only the samples quantized to
+    /// the upper level are
+    fn is_on(&self, (sample, level): QuantizedSample) -> bool {
+        match sample {
+            Some(_) if self.color_output.mode != ColorMode::Monochrome => true,
+            Some(_) => level == Some(1.),
+            None => false,
+        }
+    }
+
+    fn terminal_color(&self, (sample, _): QuantizedSample) -> Option<crossterm::style::Color> {
+        sample.and_then(|shading| self.color_output.mode.to_terminal_color(shading.color))
+    }
+
+    fn ascii_cell(&self, sample: QuantizedSample) -> Cell {
+        let (Some(_), Some(level)) = sample else {
+            return Cell::EMPTY;
+        };
+        let glyph = self.ramp.glyph_for_level(level);
+        match (self.terminal_color(sample), self.color_output.target) {
+            (None, _) => Cell::plain(glyph),
+            (Some(color), ColorTarget::Foreground) => Cell {
+                glyph,
+                foreground: Some(color),
+                background: None,
+            },
+            (Some(color), ColorTarget::Background) => Cell {
+                glyph: ' ',
+                foreground: None,
+                background: Some(color),
+            },
+        }
+    }
+
+    fn half_block_cell(&self, top: QuantizedSample, bottom: QuantizedSample) -> Cell {
+        match (self.is_on(top), self.is_on(bottom)) {
+            (false, false) => Cell::EMPTY,
+            (true, false) => Cell {
+                glyph: UPPER_HALF_BLOCK,
+                foreground: self.terminal_color(top),
+                background: None,
+            },
+            (false, true) => Cell {
+                glyph: LOWER_HALF_BLOCK,
+                foreground: self.terminal_color(bottom),
+                background: None,
+            },
+            (true, true) => match self.color_output.mode {
+                ColorMode::Monochrome => Cell::plain(FULL_BLOCK),
+                _ => Cell {
+                    glyph: UPPER_HALF_BLOCK,
+                    foreground: self.terminal_color(top),
+                    background: self.terminal_color(bottom),
+                },
+            },
+        }
+    }
+
+    fn braille_cell(&self, samples: &[[QuantizedSample; 2]; 4]) -> Cell {
+        let mut dots = 0;
+        let mut color_sum = Rgb::BLACK;
+        for (dot_row, samples_row) in BRAILLE_DOTS.iter().zip(samples) {
+            for (dot, sample) in dot_row.iter().zip(samples_row) {
+                if self.is_on(*sample) {
+                    dots |= dot;
+                    if let (Some(Shading { color, .. }), _) = sample {
+                        color_sum = Rgb::new(
+                            color_sum.r + color.r,
+                            color_sum.g + color.g,
+                            color_sum.b + color.b,
+                        );
+                    }
+                }
+            }
+        }
+        if dots == 0 {
+            return Cell::EMPTY;
+        }
+        let average_color = color_sum.scale(1. / dots.count_ones() as f32);
+        Cell {
+            glyph: char::from_u32(BRAILLE_BLANK + dots).unwrap(),
+            foreground: self.color_output.mode.to_terminal_color(average_color),
+            background: None,
+        }
+    }
+}
+
+impl Default for CellPresenter {
+    fn default() -> CellPresenter {
+        CellPresenter {
+            mode: RenderMode::Ascii,
+            color_output: ColorOutput::default(),
+            ramp: CharRamp::default(),
+            dithering: Dithering::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cells {
+    use super::*;
+    use crossterm::style::Color;
+
+    fn lit(color: Rgb) -> Option<Shading> {
+        Some(Shading {
+            intensity: 1.,
+            color,
+            normal: unit_z(),
+            depth: 1.,
+            object_id: None,
+        })
+    }
+    /// presents a frame of the samples of a single cell
+    fn present_cell(presenter: &CellPresenter, samples: Vec<Option<Shading>>) -> Cell {
+        let (columns, rows) = presenter.mode.samples_per_cell();
+        let viewport = presenter.frame_viewport(&Viewport::new(1, 1));
+        assert_eq!(samples.len(), columns * rows);
+        presenter
+            .present(&FrameBuffer::new(viewport, samples))
+            .get(0, 0)
+    }
+    #[test]
+    fn test_braille_dots() {
+        let presenter = CellPresenter {
+            mode: RenderMode::Braille,
+            ..CellPresenter::default()
+        };
+        let only = |index: usize| {
+            let mut samples = vec![None; 8];
+            samples[index] = lit(Rgb::WHITE);
+            present_cell(&presenter, samples).glyph
+        };
+        // the samples are row by row, two per row
+        assert_eq!(only(0), '\u{2801}');
+        assert_eq!(only(1), '\u{2808}');
+        assert_eq!(only(4), '\u{2804}');
+        assert_eq!(only(6), '\u{2840}');
+        assert_eq!(only(7), '\u{2880}');
+        assert_eq!(present_cell(&presenter, vec![None; 8]), Cell::EMPTY);
+        assert_eq!(
+            present_cell(&presenter, vec![lit(Rgb::WHITE); 8]).glyph,
+            '\u{28ff}'
+        );
+    }
+    #[test]
+    fn test_half_blocks() {
+        let presenter = CellPresenter {
+            mode: RenderMode::HalfBlock,
+            color_output: ColorOutput {
+                mode: ColorMode::TrueColor,
+                ..ColorOutput::default()
+            },
+            ..CellPresenter::default()
+        };
+        let (red, blue) = (Rgb::new(1., 0., 0.), Rgb::new(0., 0., 1.));
+        let (terminal_red, terminal_blue) = (
+            Color::Rgb { r: 255, g: 0, b: 0 },
+            Color::Rgb { r: 0, g: 0, b: 255 },
+        );
+        let top_only = present_cell(&presenter, vec![lit(red), None]);
+        assert_eq!(top_only.glyph, UPPER_HALF_BLOCK);
+        assert_eq!(top_only.foreground, Some(terminal_red));
+        assert_eq!(top_only.background, None);
+        let bottom_only = present_cell(&presenter, vec![None, lit(blue)]);
+        assert_eq!(bottom_only.glyph, LOWER_HALF_BLOCK);
+        assert_eq!(bottom_only.foreground, Some(terminal_blue));
+        let both = present_cell(&presenter, vec![lit(red), lit(blue)]);
+        assert_eq!(both.glyph, UPPER_HALF_BLOCK);
+        assert_eq!(both.foreground, Some(terminal_red));
+        assert_eq!(both.background, Some(terminal_blue));
+        let monochrome = CellPresenter {
+            mode: RenderMode::HalfBlock,
+            ..CellPresenter::default()
+        };
+        let both = present_cell(&monochrome, vec![lit(red), lit(blue)]);
+        assert_eq!(both, Cell::plain(FULL_BLOCK));
+    }
+}
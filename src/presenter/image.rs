@@ -0,0 +1,158 @@
+use crate::png;
+use crate::prelude::*;
+use std::path::Path;
+
+/// an RGB image, stored row by row from the top
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// a black image
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![Rgb::BLACK; width * height],
+        }
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn get(&self, row: usize, col: usize) -> Rgb {
+        self.pixels[row * self.width + col]
+    }
+    pub fn set(&mut self, row: usize, col: usize, color: Rgb) {
+        self.pixels[row * self.width + col] = color;
+    }
+    pub fn pixels(&self) -> &[Rgb] {
+        &self.pixels
+    }
+    /// the red, green and blue bytes of every pixel
+    pub fn rgb_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| {
+                let (r, g, b) = pixel.to_u8();
+                [r, g, b]
+            })
+            .collect()
+    }
+    /// the brightness of every pixel as a byte, with the Rec. 709 weights of the channels
+    pub fn luma_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .map(|pixel| {
+                let luma = 0.2126 * pixel.r + 0.7152 * pixel.g + 0.0722 * pixel.b;
+                (luma.clamp(0., 1.) * 255.).round() as u8
+            })
+            .collect()
+    }
+    /// the image as a binary PGM file, in shades of grey
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let mut pgm = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        pgm.extend(self.luma_bytes());
+        pgm
+    }
+    /// the image as a binary PPM file
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.extend(self.rgb_bytes());
+        ppm
+    }
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode_rgb(self.width, self.height, &self.rgb_bytes())
+    }
+    /// writes the image to `path`, in the format given by its extension: `pgm`, `ppm` or `png`
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let bytes = match extension.map(|extension| extension.to_ascii_lowercase()) {
+            Some(extension) if extension == "pgm" => self.to_pgm(),
+            Some(extension) if extension == "ppm" => self.to_ppm(),
+            Some(extension) if extension == "png" => self.to_png(),
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "unknown image format of `{}`, expected .pgm, .ppm or .png",
+                        path.display()
+                    ),
+                ))
+            }
+        };
+        std::fs::write(path, bytes)
+    }
+}
+
+/// turns the frame buffer into an image with one pixel per sample. The pixels are square, so the
+/// viewport is in pixels rather than terminal cells
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImagePresenter {
+    /// the colour of the pixels where the rays missed
+    pub background: Rgb,
+}
+
+impl ImagePresenter {
+    pub fn new(background: Rgb) -> ImagePresenter {
+        ImagePresenter { background }
+    }
+}
+
+impl Default for ImagePresenter {
+    fn default() -> ImagePresenter {
+        ImagePresenter::new(Rgb::BLACK)
+    }
+}
+
+impl Presenter for ImagePresenter {
+    type Output = Image;
+
+    fn frame_viewport(&self, viewport: &Viewport) -> Viewport {
+        viewport.with_h_w_ratio(1.)
+    }
+    fn present(&self, frame: &FrameBuffer) -> Image {
+        Image {
+            width: frame.width(),
+            height: frame.height(),
+            pixels: frame
+                .pixels()
+                .iter()
+                .map(|pixel| pixel.map_or(self.background, |shading| shading.color))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_image {
+    use super::*;
+    #[test]
+    fn test_render_sphere_to_image() {
+        let presenter = ImagePresenter::new(Rgb::new(0., 0., 1.));
+        let viewport = Viewport::new(40, 30).with_min_fov(0.45);
+        let frame = Renderer::default().render(
+            &Camera::default(),
+            &Scene::default(),
+            &sphere::Sphere::new(vector!(0, 0, 0), 2.),
+            &presenter.frame_viewport(&viewport),
+        );
+        let image = presenter.present(&frame);
+        assert_eq!((image.width(), image.height()), (40, 30));
+        assert_eq!(image.get(0, 0), Rgb::new(0., 0., 1.));
+        assert!(image.get(15, 20).r > 0.);
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n40 30\n255\n"));
+        assert_eq!(ppm.len(), b"P6\n40 30\n255\n".len() + 40 * 30 * 3);
+        assert!(image.to_pgm().starts_with(b"P5\n40 30\n255\n"));
+        assert!(image.to_png().starts_with(b"\x89PNG"));
+        // empty images are still valid files
+        assert!(Image::new(0, 3).to_png().starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,12 @@
+use crate::prelude::*;
+pub mod cells;
+pub mod image;
+
+/// turns the frame buffer produced by the `Renderer` into some output, e.g. terminal cells or an
+/// image. Presenters decide the resolution they need the frame to be rendered at
+pub trait Presenter {
+    type Output;
+    /// the viewport the frame has to be rendered with for an output the size of `viewport`
+    fn frame_viewport(&self, viewport: &Viewport) -> Viewport;
+    fn present(&self, frame: &FrameBuffer) -> Self::Output;
+}
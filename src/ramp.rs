@@ -0,0 +1,335 @@
+/// the fraction of the cell covered by ink for the printable ASCII characters and the block
+/// shades, measured by rasterizing DejaVu Sans Mono. Other monospace fonts differ in the details
+/// but mostly agree on the order
+const DEJAVU_SANS_MONO_COVERAGE: [(char, f32); 99] = [
+    (' ', 0.0000),
+    ('!', 0.0849),
+    ('"', 0.0657),
+    ('#', 0.2425),
+    ('$', 0.2095),
+    ('%', 0.1857),
+    ('&', 0.2290),
+    ('\'', 0.0329),
+    ('(', 0.1127),
+    (')', 0.1130),
+    ('*', 0.0956),
+    ('+', 0.1119),
+    (',', 0.0442),
+    ('-', 0.0291),
+    ('.', 0.0261),
+    ('/', 0.1091),
+    ('0', 0.2454),
+    ('1', 0.1587),
+    ('2', 0.1824),
+    ('3', 0.1901),
+    ('4', 0.1983),
+    ('5', 0.1988),
+    ('6', 0.2301),
+    ('7', 0.1449),
+    ('8', 0.2491),
+    ('9', 0.2303),
+    (':', 0.0521),
+    (';', 0.0702),
+    ('<', 0.1179),
+    ('=', 0.1230),
+    ('>', 0.1179),
+    ('?', 0.1245),
+    ('@', 0.2752),
+    ('A', 0.2176),
+    ('B', 0.2739),
+    ('C', 0.1645),
+    ('D', 0.2434),
+    ('E', 0.2206),
+    ('F', 0.1777),
+    ('G', 0.2160),
+    ('H', 0.2382),
+    ('I', 0.1755),
+    ('J', 0.1577),
+    ('K', 0.2309),
+    ('L', 0.1448),
+    ('M', 0.2754),
+    ('N', 0.2743),
+    ('O', 0.2352),
+    ('P', 0.2097),
+    ('Q', 0.2528),
+    ('R', 0.2508),
+    ('S', 0.1989),
+    ('T', 0.1572),
+    ('U', 0.2199),
+    ('V', 0.1886),
+    ('W', 0.2690),
+    ('X', 0.2003),
+    ('Y', 0.1554),
+    ('Z', 0.1973),
+    ('[', 0.1377),
+    ('\\', 0.1091),
+    (']', 0.1377),
+    ('^', 0.0707),
+    ('_', 0.0336),
+    ('`', 0.0225),
+    ('a', 0.1913),
+    ('b', 0.2173),
+    ('c', 0.1281),
+    ('d', 0.2173),
+    ('e', 0.1855),
+    ('f', 0.1453),
+    ('g', 0.2375),
+    ('h', 0.1904),
+    ('i', 0.1342),
+    ('j', 0.1428),
+    ('k', 0.1913),
+    ('l', 0.1275),
+    ('m', 0.2160),
+    ('n', 0.1631),
+    ('o', 0.1763),
+    ('p', 0.2169),
+    ('q', 0.2171),
+    ('r', 0.1068),
+    ('s', 0.1495),
+    ('t', 0.1411),
+    ('u', 0.1629),
+    ('v', 0.1379),
+    ('w', 0.1877),
+    ('x', 0.1463),
+    ('y', 0.1718),
+    ('z', 0.1423),
+    ('{', 0.1520),
+    ('|', 0.1198),
+    ('}', 0.1504),
+    ('~', 0.0606),
+    ('░', 0.1858),
+    ('▒', 0.5017),
+    ('▓', 0.8183),
+    ('█', 1.0000),
+];
+
+/// the ink coverage of `glyph` in DejaVu Sans Mono, if it was measured
+pub fn builtin_coverage(glyph: char) -> Option<f32> {
+    DEJAVU_SANS_MONO_COVERAGE
+        .iter()
+        .find(|(measured, _)| *measured == glyph)
+        .map(|(_, coverage)| *coverage)
+}
+
+/// measures the ink coverage of a glyph from a bitmap of it, given as rows of text where every
+/// character other than a space or a `.` is an inked pixel. This is meant for calibrating a ramp
+/// against the font of a particular terminal
+pub fn measure_coverage(bitmap: &[&str]) -> f32 {
+    let (inked, total) = bitmap
+        .iter()
+        .flat_map(|row| row.chars())
+        .fold((0, 0), |(inked, total), pixel| {
+            (inked + usize::from(pixel != ' ' && pixel != '.'), total + 1)
+        });
+    if total == 0 {
+        0.
+    } else {
+        inked as f32 / total as f32
+    }
+}
+
+/// maps light intensities to characters of increasing brightness
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharRamp {
+    /// from the darkest to the brightest
+    glyphs: Vec<char>,
+    /// the intensity each glyph stands for, increasing, between 0 and 1
+    levels: Vec<f32>,
+    /// the intensity is raised to `1 / gamma` before picking a glyph, values above 1 brighten the
+    /// mid tones
+    pub gamma: f32,
+    /// stretches the intensities away from the mid grey, values above 1 increase the contrast
+    pub contrast: f32,
+}
+
+impl CharRamp {
+    /// a ramp of `glyphs`, ordered from the darkest to the brightest, with evenly spaced
+    /// brightness steps
+    pub fn from_glyphs(glyphs: &str) -> CharRamp {
+        let glyphs: Vec<char> = glyphs.chars().collect();
+        assert!(
+            !glyphs.is_empty(),
+            "a character ramp needs at least one glyph"
+        );
+        let n_glyphs = glyphs.len() as f32;
+        CharRamp {
+            levels: (0..glyphs.len())
+                .map(|index| (index as f32 + 0.5) / n_glyphs)
+                .collect(),
+            glyphs,
+            gamma: 1.,
+            contrast: 1.,
+        }
+    }
+    /// the original 9 character ramp
+    pub fn short() -> CharRamp {
+        CharRamp::from_glyphs(",:;+*%$#@")
+    }
+    /// Paul Bourke's 70 character ramp
+    pub fn long() -> CharRamp {
+        CharRamp::from_glyphs(
+            " .'`^\",:;Il!i><~+_-?][}{1)(|\\/tfjrxnuvczXYUJCLQ0OZmwqpdbkhao*#MW&8%B@$",
+        )
+    }
+    /// the light, medium, dark shade and full block characters
+    pub fn blocks() -> CharRamp {
+        CharRamp::from_glyphs("░▒▓█")
+    }
+    pub fn with_gamma(mut self, gamma: f32) -> CharRamp {
+        self.gamma = gamma;
+        self
+    }
+    pub fn with_contrast(mut self, contrast: f32) -> CharRamp {
+        self.contrast = contrast;
+        self
+    }
+    /// reorders the glyphs by their ink coverage and spaces their levels in proportion to it, so
+    /// equal steps in intensity give equal steps in perceived brightness. Glyphs for which
+    /// `coverage` returns `None` are given a level interpolated between their closest measured
+    /// neighbours in the ramp, or the level of the only one at the ends
+    pub fn with_coverage(mut self, coverage: impl Fn(char) -> Option<f32>) -> CharRamp {
+        let coverages: Vec<Option<f32>> = self.glyphs.iter().map(|&g| coverage(g)).collect();
+        let known: Vec<f32> = coverages.iter().flatten().copied().collect();
+        if known.len() < 2 {
+            return self;
+        }
+        let min = known.iter().copied().fold(f32::MAX, f32::min);
+        let max = known.iter().copied().fold(f32::MIN, f32::max);
+        if max <= min {
+            return self;
+        }
+        let measured: Vec<Option<f32>> = coverages
+            .iter()
+            .map(|coverage| coverage.map(|coverage| (coverage - min) / (max - min)))
+            .collect();
+        // the index and level of the first measured glyph among `indices`
+        let neighbour = |mut indices: std::ops::Range<usize>, from_end: bool| {
+            let find = |index: usize| measured[index].map(|level| (index, level));
+            if from_end {
+                indices.rev().find_map(find)
+            } else {
+                indices.find_map(find)
+            }
+        };
+        let mut ramp: Vec<(char, f32)> = self
+            .glyphs
+            .iter()
+            .enumerate()
+            .map(|(index, &glyph)| {
+                let level = measured[index].unwrap_or_else(|| {
+                    let before = neighbour(0..index, true);
+                    let after = neighbour(index + 1..measured.len(), false);
+                    match (before, after) {
+                        (Some((a, level_a)), Some((b, level_b))) => {
+                            level_a + (level_b - level_a) * (index - a) as f32 / (b - a) as f32
+                        }
+                        (Some((_, level)), None) | (None, Some((_, level))) => level,
+                        (None, None) => unreachable!("at least two glyphs are measured"),
+                    }
+                });
+                (glyph, level)
+            })
+            .collect();
+        // the sort is stable, so glyphs of equal levels keep their order
+        ramp.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        self.glyphs = ramp.iter().map(|(glyph, _)| *glyph).collect();
+        self.levels = ramp.iter().map(|(_, level)| *level).collect();
+        self
+    }
+    /// calibrates the ramp against the coverage measured in DejaVu Sans Mono
+    pub fn calibrated(self) -> CharRamp {
+        self.with_coverage(builtin_coverage)
+    }
+    pub fn glyphs(&self) -> &[char] {
+        &self.glyphs
+    }
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+    /// applies the contrast and the gamma to an intensity, giving a value between 0 and 1
+    pub fn adjust(&self, intensity: f32) -> f32 {
+        (((intensity - 0.5) * self.contrast) + 0.5)
+            .clamp(0., 1.)
+            .powf(1. / self.gamma)
+    }
+    /// the glyph whose level is closest to the adjusted intensity. Negative intensities are
+    /// drawn as empty space
+    pub fn glyph(&self, intensity: f32) -> char {
+        if intensity < 0. {
+            return ' ';
+        }
+        self.glyph_for_level(self.adjust(intensity))
+    }
+    /// the glyph whose level is closest to `level`, without any adjustment
+    pub fn glyph_for_level(&self, level: f32) -> char {
+        self.glyphs[crate::dither::closest_level(&self.levels, level)]
+    }
+}
+
+impl Default for CharRamp {
+    fn default() -> CharRamp {
+        CharRamp::short()
+    }
+}
+
+#[cfg(test)]
+mod test_ramp {
+    use super::*;
+    #[test]
+    fn test_linear_ramp_matches_index_mapping() {
+        let ramp = CharRamp::short();
+        assert_eq!(ramp.glyph(0.), ',');
+        assert_eq!(ramp.glyph(0.5), '*');
+        assert_eq!(ramp.glyph(1.), '@');
+        assert_eq!(ramp.glyph(7.), '@');
+        assert_eq!(ramp.glyph(-0.1), ' ');
+    }
+    #[test]
+    fn test_calibration_orders_by_coverage() {
+        let ramp = CharRamp::from_glyphs("@.:").calibrated();
+        assert_eq!(ramp.glyphs(), &['.', ':', '@']);
+        assert_eq!(ramp.glyph(0.), '.');
+        assert_eq!(ramp.glyph(1.), '@');
+    }
+    #[test]
+    fn test_unmeasured_glyphs_stay_between_their_neighbours() {
+        let coverage = |glyph| match glyph {
+            'a' => Some(0.2),
+            'c' => Some(0.6),
+            'd' => Some(1.),
+            _ => None,
+        };
+        let assert_ramp = |ramp: CharRamp, glyphs: &str, levels: &[f32]| {
+            assert_eq!(ramp.glyphs(), glyphs.chars().collect::<Vec<_>>());
+            for (level, expected) in ramp.levels.iter().zip(levels) {
+                assert!((level - expected).abs() < 1e-5, "{:?}", ramp.levels);
+            }
+        };
+        assert_ramp(
+            CharRamp::from_glyphs("xabcdy").with_coverage(coverage),
+            "xabcdy",
+            &[0., 0., 0.25, 0.5, 1., 1.],
+        );
+        // measured glyphs out of order carry the unmeasured ones between them along
+        assert_ramp(
+            CharRamp::from_glyphs("dbay").with_coverage(coverage),
+            "aybd",
+            &[0., 0., 0.5, 1.],
+        );
+    }
+    #[test]
+    fn test_nan_coverage_does_not_panic() {
+        let ramp = CharRamp::from_glyphs(".:@").with_coverage(|glyph| match glyph {
+            ':' => Some(f32::NAN),
+            '.' => Some(0.),
+            _ => Some(1.),
+        });
+        assert_eq!(ramp.glyphs().len(), 3);
+        assert_eq!(ramp.glyph(0.), '.');
+    }
+    #[test]
+    fn test_measure_coverage() {
+        assert_eq!(measure_coverage(&["#.", ".#"]), 0.5);
+        assert_eq!(measure_coverage(&[]), 0.);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// rows of samples handed to a rendering thread at a time. The bands are small so the threads
+/// stay evenly loaded when the object only covers part of the frame
+const ROWS_PER_BAND: usize = 4;
+
+/// the ray marching stage of the render pipeline: fires the camera rays through every pixel of
+/// a viewport and shades what they hit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Renderer {
+    pub supersampling: Supersampling,
+    /// the number of threads the frame is rendered with, 0 to use every available core. The
+    /// frame is the same whatever the number of threads
+    pub threads: usize,
+}
+
+impl Renderer {
+    pub fn with_threads(self, threads: usize) -> Renderer {
+        Renderer { threads, ..self }
+    }
+    pub fn render(
+        &self,
+        camera: &Camera,
+        scene: &Scene,
+        object: &impl Object3D,
+        viewport: &Viewport,
+    ) -> FrameBuffer {
+        // the bounds are the same for every ray of the frame
+        let bounds = object.bounding_sphere();
+        FrameBuffer::new(
+            *viewport,
+            self.supersampling.sample_frame(
+                camera,
+                scene,
+                object,
+                bounds.as_ref(),
+                viewport,
+                self.thread_count(),
+            ),
+        )
+    }
+    /// the number of threads actually used, resolving 0 to the available parallelism
+    pub fn thread_count(&self) -> usize {
+        match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        }
+    }
+}
+
+/// evaluates `sample` at every (row, column) of a `width` x `height` frame, returning the results
+/// in row-major order. The rows are split into bands which `threads` scoped threads take in turn
+pub(crate) fn map_samples<T: Send>(
+    width: usize,
+    height: usize,
+    threads: usize,
+    sample: impl Fn(usize, usize) -> T + Sync,
+) -> Vec<T> {
+    let render_band = |band: usize| -> Vec<T> {
+        let rows = band * ROWS_PER_BAND..((band + 1) * ROWS_PER_BAND).min(height);
+        rows.flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(|(row, col)| sample(row, col))
+            .collect()
+    };
+    let n_bands = height.div_ceil(ROWS_PER_BAND);
+    let threads = threads.clamp(1, n_bands.max(1));
+    if threads == 1 {
+        return (0..n_bands).flat_map(render_band).collect();
+    }
+    let next_band = AtomicUsize::new(0);
+    let mut bands: Vec<(usize, Vec<T>)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut bands = vec![];
+                    loop {
+                        let band = next_band.fetch_add(1, Ordering::Relaxed);
+                        if band >= n_bands {
+                            return bands;
+                        }
+                        bands.push((band, render_band(band)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("a rendering thread panicked"))
+            .collect()
+    });
+    bands.sort_unstable_by_key(|(band, _)| *band);
+    bands.into_iter().flat_map(|(_, samples)| samples).collect()
+}
+
+#[cfg(test)]
+mod test_renderer {
+    use super::*;
+    #[test]
+    fn test_threads_render_the_same_frame() {
+        let object = Union::from_objects(boxed_vec![
+            sphere::Sphere::new(vector!(-1, 0, 0), 1.5),
+            cuboid::Cuboid::new(1., 2., 3.)
+        ]);
+        let (camera, scene) = (Camera::default(), Scene::default());
+        let viewport = Viewport::new(37, 19);
+        for supersampling in [
+            Supersampling::Off,
+            Supersampling::Adaptive {
+                columns: 2,
+                rows: 2,
+            },
+        ] {
+            let renderer = Renderer {
+                supersampling,
+                threads: 1,
+            };
+            let single = renderer.render(&camera, &scene, &object, &viewport);
+            let multi = renderer
+                .with_threads(4)
+                .render(&camera, &scene, &object, &viewport);
+            assert_eq!(single.pixels(), multi.pixels());
+        }
+    }
+}
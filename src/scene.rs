@@ -2,8 +2,153 @@
 // use crate::math::*;
 // use crate::objects;
 use crate::prelude::*;
-const MIN_DISTANCE: f32 = 0.003;
-const MAX_DISTANCE_FROM_CAMERA: f32 = 250.0;
+
+/// what the camera shows when it is inside an object
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InteriorView {
+    /// the inner side of the surface around the camera, lit as if the object did not cast
+    /// shadows on it
+    #[default]
+    BackFaces,
+    /// the object is cut away up to where the rays leave it, showing what is behind
+    Cutaway,
+}
+
+/// the parameters of the sphere tracing of camera rays, which trade quality for speed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MarchSettings {
+    /// rays closer than this to the surface hit it
+    pub min_distance: f32,
+    /// rays which get further than this from the camera miss
+    pub max_distance: f32,
+    /// rays which take more steps than this miss
+    pub max_iterations: u32,
+    /// the hit distance grows by this much per unit of distance from the camera, since a
+    /// sample covers more of the far surfaces. Rays grazing thin features then stop on them
+    /// instead of creeping along until they run out of steps
+    pub relative_epsilon: f32,
+    /// every step is this many times the SDF. Above 1 the rays march faster, and fall back to
+    /// plain steps when one jumps over the surface
+    pub over_relaxation: f32,
+    pub interior: InteriorView,
+}
+
+impl MarchSettings {
+    /// how close a ray which went `distance_from_camera` has to get to the surface to hit it
+    pub fn hit_distance(&self, distance_from_camera: f32) -> f32 {
+        self.min_distance
+            .max(self.relative_epsilon * distance_from_camera)
+    }
+}
+
+impl Default for MarchSettings {
+    fn default() -> MarchSettings {
+        MarchSettings {
+            min_distance: 0.003,
+            max_distance: 250.,
+            max_iterations: 275,
+            relative_epsilon: 0.0005,
+            over_relaxation: 1.25,
+            interior: InteriorView::default(),
+        }
+    }
+}
+
+/// the point where a camera ray meets the surface of an object
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SurfaceHit {
+    pub position: Vector,
+    /// the normalised normal of the surface at `position`, on the side the ray came from. It
+    /// points outwards, unless the ray hit the surface from inside the object
+    pub normal: Vector,
+    /// whether the ray started inside the object and hit its surface from within
+    pub inside: bool,
+}
+
+/// what a camera ray sees where it hits an object
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shading {
+    /// the light intensity reflected towards the camera, 1 is the brightest character of the ramp
+    pub intensity: f32,
+    /// the base colour of the object scaled by the intensity
+    pub color: Rgb,
+    /// the normal of the surface at the hit point
+    pub normal: Vector,
+    /// the distance from the camera to the hit point
+    pub depth: f32,
+    /// the id of the tagged object which was hit, if any
+    pub object_id: Option<u32>,
+}
+
+/// everything in the world apart from the objects themselves: the lights and how surfaces respond
+/// to them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scene {
+    pub lights: Vec<Light>,
+    /// the Blinn-Phong highlight added on top of the Lambertian diffuse term, if any
+    pub specular: Option<Specular>,
+    pub shadows: Shadows,
+    /// darkens the ambient lights in concave regions, if any
+    pub ambient_occlusion: Option<AmbientOcclusion>,
+}
+
+impl Scene {
+    /// the light intensity reflected towards the viewer from `hit` on the surface of `object`.
+    /// `to_viewer` should be normalised
+    pub fn shade(&self, object: &impl Object3D, hit: &SurfaceHit, to_viewer: &Vector) -> f32 {
+        // the inner side of a surface is surrounded by the object, which would shadow and occlude
+        // all of it, so both are left out there
+        let ambient_factor = match self.ambient_occlusion {
+            Some(ambient_occlusion) if !hit.inside => ambient_occlusion.ambient_factor(object, hit),
+            _ => 1.,
+        };
+        let shadows = if hit.inside {
+            Shadows::None
+        } else {
+            self.shadows
+        };
+        self.lights
+            .iter()
+            .map(|light| match light.direction_from(&hit.position) {
+                Some((to_light, light_distance)) => {
+                    let unshadowed = shade_single_light(
+                        &hit.normal,
+                        &to_light,
+                        to_viewer,
+                        self.specular.as_ref(),
+                    );
+                    // surfaces facing away from the light do not need a shadow ray
+                    if unshadowed == 0. {
+                        return 0.;
+                    }
+                    light.intensity()
+                        * unshadowed
+                        * shadow_factor(object, hit, &to_light, light_distance, shadows)
+                }
+                None => light.intensity() * ambient_factor,
+            })
+            .sum()
+    }
+}
+
+impl Default for Scene {
+    /// a key light shining from the upper left of a camera looking along the y axis, plus a
+    /// little ambient light so faces turned away from it are still visible
+    fn default() -> Scene {
+        Scene {
+            lights: vec![
+                Light::Directional {
+                    direction: vector!(1, 2, -1.5),
+                    intensity: 0.75,
+                },
+                Light::Ambient { intensity: 0.25 },
+            ],
+            specular: Some(Specular::default()),
+            shadows: Shadows::Soft { softness: 8. },
+            ambient_occlusion: Some(AmbientOcclusion::default()),
+        }
+    }
+}
 
 pub struct Camera {
     /// camera position in outside world coordinates
@@ -11,84 +156,233 @@ pub struct Camera {
     /// the matrix which converts from camera coordinates to outside world coordinates
     /// x is width to the right, y is the direction the camera is facing, z is height up
     pub matrix: Matrix,
+    /// how the normal vector is found at the points the rays hit
+    pub normals: NormalEstimator,
+    pub march: MarchSettings,
 }
 
 impl Camera {
-    pub fn get_ray_from_camera(&self, y: i32, x: i32) -> Vector {
-        let ray_camera_reference_frame = vector!(
-            FOV / WIDTH as f32 * ((x - WIDTH / 2) as f32),
-            1,
-            -FOV / WIDTH as f32 * H_W_RATIO * ((y - HEIGHT / 2) as f32)
-        );
-
-        (self.matrix * ray_camera_reference_frame).normalise()
-    }
-    /// computes the normal vector to the surface which intersects the direction vector, or
-    /// returns none if no intersection or the camera is inside the object itself
-    /// direction should be normalised
-    fn compute_intersection(&self, object: &impl Object3D, direction: &Vector) -> Option<Vector> {
-        let mut ray_front = self.position;
-        let mut distance = object.signed_distance_function(&ray_front);
-        // we are inside the object
-        if distance < 0.0 {
-            return None;
+    /// a camera at `position` facing `target`, rolled so that `up` points upwards on the screen.
+    /// `up` must not be parallel to the viewing direction
+    pub fn look_at(position: Vector, target: Vector, up: Vector) -> Camera {
+        let forward = (target - position).normalise();
+        let right = forward.cross(&up).normalise();
+        let screen_up = right.cross(&forward);
+        Camera {
+            position,
+            matrix: matrix_from_columns([right, forward, screen_up]),
+            normals: NormalEstimator::default(),
+            march: MarchSettings::default(),
         }
-        // if we are not inside the object alreay, there is no way to enter it with a properly
-        // defined SDF
-        for _ in 0..MAX_ITERATIONS {
-            if distance < MIN_DISTANCE {
-                let (dx, dy, dz) = (
-                    MIN_DISTANCE * vector!(0.1, 0, 0),
-                    MIN_DISTANCE * vector!(0, 0.1, 0),
-                    MIN_DISTANCE * vector!(0, 0, 0.1),
-                );
-                // return the normal vector to the surface
-                return Some(
-                    vector!(
-                        (object.signed_distance_function(&(ray_front + dx)) - distance)
-                            / MIN_DISTANCE,
-                        (object.signed_distance_function(&(ray_front + dy)) - distance)
-                            / MIN_DISTANCE,
-                        (object.signed_distance_function(&(ray_front + dz)) - distance)
-                            / MIN_DISTANCE
-                    )
-                    .normalise(),
-                );
+    }
+    pub fn with_march(self, march: MarchSettings) -> Camera {
+        Camera { march, ..self }
+    }
+    pub fn with_normals(self, normals: NormalEstimator) -> Camera {
+        Camera { normals, ..self }
+    }
+    /// the normalised direction, in world coordinates, of the ray through the cell at (`row`,
+    /// `col`) of the viewport
+    pub fn get_ray_from_camera(&self, viewport: &Viewport, row: usize, col: usize) -> Vector {
+        self.get_ray_through(viewport, row as f32, col as f32)
+    }
+    /// like `get_ray_from_camera`, but through any point of the viewport. The centre of the
+    /// cell at (`row`, `col`) is at integer coordinates
+    pub fn get_ray_through(&self, viewport: &Viewport, row: f32, col: f32) -> Vector {
+        (self.matrix * viewport.ray_through(row, col)).normalise()
+    }
+    /// computes the point where the ray along `direction` hits the surface of the object and the
+    /// normal vector there, or returns none if no intersection. If the camera is inside the
+    /// object, what the ray hits depends on `MarchSettings::interior`. direction should be
+    /// normalised
+    pub fn compute_intersection(
+        &self,
+        object: &impl Object3D,
+        direction: &Vector,
+    ) -> Option<SurfaceHit> {
+        self.compute_intersection_within(object, object.bounding_sphere().as_ref(), direction)
+    }
+    /// like `compute_intersection`, with the bounds of the object given. Finding them takes an
+    /// inversion and a decomposition of its orientation matrix, so renderers find them once per
+    /// frame rather than once per ray
+    pub fn compute_intersection_within(
+        &self,
+        object: &impl Object3D,
+        bounds: Option<&BoundingSphere>,
+        direction: &Vector,
+    ) -> Option<SurfaceHit> {
+        let march = &self.march;
+        // inside the object, the rays march on the distance to the surface around them, which is
+        // the negated SDF
+        let mut inside = object.signed_distance_function(&self.position) < 0.0;
+        // rays missing the bounds of the object can not hit it, and the ones which do are only
+        // marched while inside them
+        let (mut t, mut max_distance) = (0., march.max_distance);
+        if let Some(bounds) = bounds {
+            let (entry, exit) = bounds.ray_interval(&self.position, direction)?;
+            t = entry;
+            max_distance = max_distance.min(exit);
+        }
+        let mut relaxation = march.over_relaxation;
+        let (mut previous_t, mut previous_distance) = (t, 0_f32);
+        let mut leaving = false;
+        for _ in 0..march.max_iterations {
+            let ray_front = self.position + t * direction;
+            let distance = if inside {
+                -object.signed_distance_function(&ray_front)
             } else {
-                // do the marching
-                ray_front += (*direction) * distance;
+                object.signed_distance_function(&ray_front)
+            };
+            // right after stepping out of a cut away object, the ray is still next to the surface
+            // it left, which it can not hit until it got clear of it
+            if leaving {
+                let hit_distance = march.hit_distance(t);
+                if distance < 2. * hit_distance {
+                    t += distance.max(hit_distance);
+                    (previous_t, previous_distance) = (t, 0.);
+                    if t > max_distance {
+                        return None;
+                    }
+                    continue;
+                }
+                leaving = false;
+            }
+            // the relaxed step went through the surface, or the spheres free of surface around
+            // the last two points do not overlap so it may have jumped over it. Go back and march
+            // with plain steps
+            if relaxation > 1.
+                && (distance < 0. || t - previous_t > previous_distance.abs() + distance.abs())
+            {
+                relaxation = 1.;
+                t = previous_t + previous_distance;
+                continue;
+            }
+            if distance < march.hit_distance(t) {
+                if inside && march.interior == InteriorView::Cutaway {
+                    inside = false;
+                    leaving = true;
+                    continue;
+                }
+                // return the hit point and the normal vector to the surface
+                let normal = self
+                    .normals
+                    .estimate(object, &ray_front, march.min_distance);
+                return Some(SurfaceHit {
+                    position: ray_front,
+                    normal: if inside { -normal } else { normal },
+                    inside,
+                });
             }
+            // do the marching
+            (previous_t, previous_distance) = (t, distance);
+            t += relaxation * distance;
             // we are too far from the camera
-            if (ray_front - self.position).norm() > MAX_DISTANCE_FROM_CAMERA {
+            if t > max_distance {
                 return None;
             }
-            distance = object.signed_distance_function(&ray_front);
         }
         None
     }
 
-    pub fn compute_light_intensity(&self, object: &impl Object3D, direction: &Vector) -> char {
-        let ascii_table: Vec<char> = ",:;+*@%$#@".chars().collect();
-        let n_chars = ascii_table.len();
-        match self.compute_intersection(object, direction) {
-            Some(normal_vec) => {
-                let intensity = normal_vec.dot(&(vector!(0, 0, 0) - (*direction)).normalise());
-                let index = intensity * (n_chars as f32);
-                if index < 0.0 {
-                    return ' ';
-                } else if index > n_chars as f32 - 1.0 {
-                    return ascii_table[n_chars - 1];
-                }
-                ascii_table[index as usize]
-            }
+    /// the shaded surface seen along `direction`, or `None` if the ray does not hit the object
+    pub fn shade_ray(
+        &self,
+        scene: &Scene,
+        object: &impl Object3D,
+        direction: &Vector,
+    ) -> Option<Shading> {
+        self.shade_ray_within(scene, object, object.bounding_sphere().as_ref(), direction)
+    }
+    /// like `shade_ray`, with the bounds of the object given, see `compute_intersection_within`
+    pub fn shade_ray_within(
+        &self,
+        scene: &Scene,
+        object: &impl Object3D,
+        bounds: Option<&BoundingSphere>,
+        direction: &Vector,
+    ) -> Option<Shading> {
+        let hit = self.compute_intersection_within(object, bounds, direction)?;
+        let intensity = scene.shade(object, &hit, &(-direction.normalise()));
+        let base_color = object.color(&hit.position).unwrap_or(Rgb::WHITE);
+        Some(Shading {
+            intensity,
+            color: base_color.scale(intensity),
+            normal: hit.normal,
+            depth: (hit.position - self.position).norm(),
+            object_id: object.object_id(&hit.position),
+        })
+    }
+
+    pub fn compute_light_intensity(
+        &self,
+        scene: &Scene,
+        object: &impl Object3D,
+        direction: &Vector,
+    ) -> char {
+        match self.shade_ray(scene, object, direction) {
+            Some(shading) => CharRamp::default().glyph(shading.intensity),
             None => ' ',
         }
     }
+}
 
-    pub fn default() -> Camera {
+impl Default for Camera {
+    fn default() -> Camera {
         Camera {
             matrix: Matrix::identity(),
             position: -10.0 * vector!(0, 1, 0),
+            normals: NormalEstimator::default(),
+            march: MarchSettings::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod test_scene {
+    use super::*;
+    #[test]
+    fn test_over_relaxed_march_does_not_jump_over_thin_objects() {
+        let slab = Intersection::from_objects(boxed_vec![
+            plane::Plane::new(-0.025 * unit_y(), -unit_y()),
+            plane::Plane::new(0.025 * unit_y(), unit_y())
+        ]);
+        for over_relaxation in [1., 1.9] {
+            let camera = Camera::default().with_march(MarchSettings {
+                over_relaxation,
+                ..MarchSettings::default()
+            });
+            let hit = camera
+                .compute_intersection(&slab, &vector!(0.05, 1, 0.1).normalise())
+                .expect("the ray should hit the slab");
+            assert!(
+                (hit.position.y + 0.025).abs() < 0.01,
+                "hit at {}",
+                hit.position
+            );
+        }
+    }
+    #[test]
+    fn test_camera_inside_an_object() {
+        let object = Union::from_objects(boxed_vec![
+            sphere::Sphere::new(vector!(0, -10, 0), 2.),
+            sphere::Sphere::new(vector!(0, 0, 0), 1.)
+        ]);
+        let direction = vector!(0, 1, 0);
+        let hit = Camera::default()
+            .compute_intersection(&object, &direction)
+            .expect("the inside of the sphere should be visible");
+        assert!(hit.inside);
+        assert!((hit.position.y + 8.).abs() < 0.01);
+        assert!((hit.normal - vector!(0, -1, 0)).norm() < 1e-2);
+
+        let camera = Camera::default().with_march(MarchSettings {
+            interior: InteriorView::Cutaway,
+            ..MarchSettings::default()
+        });
+        let hit = camera
+            .compute_intersection(&object, &direction)
+            .expect("the sphere behind the cut away one should be visible");
+        assert!(!hit.inside);
+        assert!((hit.position.y + 1.).abs() < 0.01);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::prelude::*;
+use crate::renderer::map_samples;
+
+/// neighbouring samples whose normals have a smaller dot product than this are on different
+/// faces, and the cells between them are supersampled in the adaptive mode
+const NORMAL_EDGE_THRESHOLD: f32 = 0.9;
+
+/// how many rays are fired per sample of the viewport to smooth the silhouettes and edges
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Supersampling {
+    /// a single ray through the centre of every sample
+    #[default]
+    Off,
+    /// a grid of `columns` x `rows` rays through every sample, whose results are averaged
+    Grid { columns: usize, rows: usize },
+    /// a single ray through every sample first, then a `columns` x `rows` grid only for the
+    /// samples on a silhouette or an edge, i.e. whose neighbours differ in hit or miss or in their
+    /// normal
+    Adaptive { columns: usize, rows: usize },
+}
+
+/// shades a `columns` x `rows` grid of rays through the sample at (`row`, `col`) and averages
+/// them. Rays missing the object count as black, so samples partially covering the object are
+/// darkened in proportion to the part they miss
+fn sample_grid(
+    camera: &Camera,
+    scene: &Scene,
+    object: &impl Object3D,
+    bounds: Option<&BoundingSphere>,
+    viewport: &Viewport,
+    (row, col): (usize, usize),
+    (columns, rows): (usize, usize),
+) -> Option<Shading> {
+    let (mut intensity, mut color, mut normal) = (0., Rgb::BLACK, vector!(0, 0, 0));
+    // the depth and object of the averaged sample are those of the closest ray
+    let mut closest: Option<Shading> = None;
+    for sub_row in 0..rows {
+        for sub_col in 0..columns {
+            let direction = camera.get_ray_through(
+                viewport,
+                row as f32 + (sub_row as f32 + 0.5) / rows as f32 - 0.5,
+                col as f32 + (sub_col as f32 + 0.5) / columns as f32 - 0.5,
+            );
+            if let Some(shading) = camera.shade_ray_within(scene, object, bounds, &direction) {
+                intensity += shading.intensity;
+                color = Rgb::new(
+                    color.r + shading.color.r,
+                    color.g + shading.color.g,
+                    color.b + shading.color.b,
+                );
+                normal += shading.normal;
+                if closest.is_none_or(|closest| shading.depth < closest.depth) {
+                    closest = Some(shading);
+                }
+            }
+        }
+    }
+    let closest = closest?;
+    let n_rays = (columns * rows) as f32;
+    Some(Shading {
+        intensity: intensity / n_rays,
+        color: color.scale(1. / n_rays),
+        normal: average_normal(normal, closest.normal),
+        ..closest
+    })
+}
+
+/// the direction of the summed normals of the rays of a sample. Normals of opposite faces, e.g.
+/// of both sides of a thin slab, can cancel out, and then the normal of the closest ray is used
+fn average_normal(sum: Vector, closest: Vector) -> Vector {
+    if sum.norm() < 1e-3 {
+        closest
+    } else {
+        sum.normalise()
+    }
+}
+
+/// whether two neighbouring samples lie on different sides of a silhouette or an edge
+fn is_edge(a: &Option<Shading>, b: &Option<Shading>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.normal.dot(&b.normal) < NORMAL_EDGE_THRESHOLD,
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+impl Supersampling {
+    /// shades every sample of the viewport, in row-major order, spread over `threads` threads.
+    /// `bounds` are those of the object, found once for the whole frame
+    pub fn sample_frame(
+        self,
+        camera: &Camera,
+        scene: &Scene,
+        object: &impl Object3D,
+        bounds: Option<&BoundingSphere>,
+        viewport: &Viewport,
+        threads: usize,
+    ) -> Vec<Option<Shading>> {
+        let (width, height) = (viewport.width, viewport.height);
+        let sample =
+            |position, grid| sample_grid(camera, scene, object, bounds, viewport, position, grid);
+        match self {
+            Supersampling::Off => map_samples(width, height, threads, |row, col| {
+                sample((row, col), (1, 1))
+            }),
+            Supersampling::Grid { columns, rows } => {
+                map_samples(width, height, threads, |row, col| {
+                    sample((row, col), (columns, rows))
+                })
+            }
+            Supersampling::Adaptive { columns, rows } => {
+                // the edges are only known once every single ray sample is in, so the frame is
+                // rendered in two passes
+                let single = map_samples(width, height, threads, |row, col| {
+                    sample((row, col), (1, 1))
+                });
+                map_samples(width, height, threads, |row, col| {
+                    let index = row * width + col;
+                    let on_edge = (col + 1 < width && is_edge(&single[index], &single[index + 1]))
+                        || (col > 0 && is_edge(&single[index], &single[index - 1]))
+                        || (index + width < single.len()
+                            && is_edge(&single[index], &single[index + width]))
+                        || (index >= width && is_edge(&single[index], &single[index - width]));
+                    if on_edge {
+                        sample((row, col), (columns, rows))
+                    } else {
+                        single[index]
+                    }
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_supersampling {
+    use super::*;
+
+    fn sample_sphere(supersampling: Supersampling, viewport: &Viewport) -> Vec<Option<Shading>> {
+        let sphere = sphere::Sphere::new(vector!(0, 0, 0), 2.);
+        supersampling.sample_frame(
+            &Camera::default(),
+            &Scene::default(),
+            &sphere,
+            sphere.bounding_sphere().as_ref(),
+            viewport,
+            1,
+        )
+    }
+    #[test]
+    fn test_single_ray_grid_matches_off() {
+        let viewport = Viewport::new(30, 12);
+        assert_eq!(
+            sample_sphere(
+                Supersampling::Grid {
+                    columns: 1,
+                    rows: 1
+                },
+                &viewport
+            ),
+            sample_sphere(Supersampling::Off, &viewport)
+        );
+    }
+    #[test]
+    fn test_adaptive_only_supersamples_edges() {
+        let viewport = Viewport::new(30, 12);
+        let (columns, rows) = (3, 3);
+        let off = sample_sphere(Supersampling::Off, &viewport);
+        let grid = sample_sphere(Supersampling::Grid { columns, rows }, &viewport);
+        let adaptive = sample_sphere(Supersampling::Adaptive { columns, rows }, &viewport);
+        let width = viewport.width;
+        let (mut n_edges, mut n_smoothed) = (0, 0);
+        for index in 0..off.len() {
+            let (row, col) = (index / width, index % width);
+            let neighbours = [
+                (row > 0).then(|| index - width),
+                (row + 1 < viewport.height).then(|| index + width),
+                (col > 0).then(|| index - 1),
+                (col + 1 < width).then(|| index + 1),
+            ];
+            let on_edge = neighbours
+                .into_iter()
+                .flatten()
+                .any(|neighbour| is_edge(&off[index], &off[neighbour]));
+            if on_edge {
+                n_edges += 1;
+                assert_eq!(adaptive[index], grid[index]);
+                if off[index].is_none() && grid[index].is_some() {
+                    n_smoothed += 1;
+                }
+            } else {
+                assert_eq!(adaptive[index], off[index]);
+            }
+        }
+        assert!(n_edges > 0 && n_edges < off.len() / 2);
+        // some samples beside the silhouette are partially covered by the sphere
+        assert!(n_smoothed > 0);
+    }
+    #[test]
+    fn test_cancelling_normals_fall_back_to_the_closest() {
+        assert_eq!(average_normal(vector!(0, 0, 0), unit_y()), unit_y());
+        assert_eq!(average_normal(vector!(0, 0, 2), unit_y()), unit_z());
+    }
+}
@@ -1,8 +1,8 @@
-use crossterm::{cursor, queue, style, ExecutableCommand};
+use crossterm::{cursor, execute, queue, style};
+use std::io::Write;
 use std::time;
 
 use crate::prelude::*;
-use std::io::Write;
 /// reduces the frame rate to a set number, fps, so there is less visual tearing in the terminal
 pub fn fps_cap(fps: u32, beginning_of_frame: &time::Instant) {
     let time_for_one_frame_ms: f32 = 1000.0 / fps as f32;
@@ -26,19 +26,145 @@ pub fn print_to_screen(
     )?;
     Ok(())
 }
-/// draws the border of the screen buffer
-pub fn initialize_screen_buffer() -> [[char; WIDTH as usize]; HEIGHT as usize] {
-    let mut screen_buffer = [[' '; WIDTH as usize]; HEIGHT as usize];
-    for i in 0..WIDTH as usize {
-        screen_buffer[0][i] = '-';
-        screen_buffer[(HEIGHT - 1) as usize][i] = '-';
+/// prints a row of cells starting at `coordinates`, given as (row, column). Colour escape codes
+/// are only emitted where the colour changes, and the colours are reset afterwards
+pub fn print_cells(
+    stdout: &mut impl Write,
+    coordinates: (u16, u16),
+    cells: &[Cell],
+) -> std::io::Result<()> {
+    queue!(stdout, cursor::MoveTo(coordinates.1, coordinates.0))?;
+    write_cells(stdout, cells)
+}
+/// like `print_cells`, but at the current position of the cursor, so it also works outside a
+/// terminal
+pub fn write_cells(stdout: &mut impl Write, cells: &[Cell]) -> std::io::Result<()> {
+    let (mut foreground, mut background) = (None, None);
+    let mut run = String::new();
+    for cell in cells {
+        if cell.foreground != foreground || cell.background != background {
+            queue!(stdout, style::Print(&run))?;
+            run.clear();
+            if cell.foreground != foreground {
+                let color = cell.foreground.unwrap_or(style::Color::Reset);
+                queue!(stdout, style::SetForegroundColor(color))?;
+                foreground = cell.foreground;
+            }
+            if cell.background != background {
+                let color = cell.background.unwrap_or(style::Color::Reset);
+                queue!(stdout, style::SetBackgroundColor(color))?;
+                background = cell.background;
+            }
+        }
+        run.push(cell.glyph);
     }
-    for buffer_row in screen_buffer.iter_mut().take(HEIGHT as usize) {
-        buffer_row[0] = '|';
-        buffer_row[(WIDTH - 1) as usize] = '|';
+    queue!(stdout, style::Print(&run))?;
+    if foreground.is_some() || background.is_some() {
+        queue!(stdout, style::ResetColor)?;
     }
+    Ok(())
+}
+/// unchanged cells between two changed runs of a row which are reprinted rather than skipped,
+/// since a cursor move costs about as many bytes
+const MAX_UNCHANGED_GAP: usize = 8;
+
+/// draws screen buffers to the terminal, emitting only the runs of cells which changed since the
+/// previous frame. Every frame is wrapped in a synchronized update so the terminal shows it all at
+/// once, without tearing
+#[derive(Default)]
+pub struct ScreenWriter {
+    previous: Option<ScreenBuffer>,
+}
+
+impl ScreenWriter {
+    pub fn new() -> ScreenWriter {
+        ScreenWriter::default()
+    }
+    /// forgets the previous frame, so the next one is drawn in full. Needed whenever the screen
+    /// was changed behind the writer's back, e.g. cleared after a resize
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+    /// queues the changes needed to turn the previous frame into `screen_buffer`, with its top
+    /// left corner at the top left of the terminal
+    pub fn draw(
+        &mut self,
+        out: &mut impl Write,
+        screen_buffer: &ScreenBuffer,
+    ) -> std::io::Result<()> {
+        let previous = self.previous.take().filter(|previous| {
+            previous.width() == screen_buffer.width() && previous.height() == screen_buffer.height()
+        });
+        queue!(out, crossterm::terminal::BeginSynchronizedUpdate)?;
+        for row in 0..screen_buffer.height() {
+            let cells = screen_buffer.row(row);
+            let runs = match &previous {
+                Some(previous) => changed_runs(previous.row(row), cells),
+                None => std::iter::once(0..cells.len()).collect(),
+            };
+            for run in runs {
+                print_cells(out, (row as u16, run.start as u16), &cells[run])?;
+            }
+        }
+        queue!(out, crossterm::terminal::EndSynchronizedUpdate)?;
+        self.previous = Some(screen_buffer.clone());
+        Ok(())
+    }
+}
+
+/// the ranges of columns where `current` differs from `previous`, with runs separated by at most
+/// `MAX_UNCHANGED_GAP` unchanged cells merged together
+fn changed_runs(previous: &[Cell], current: &[Cell]) -> Vec<std::ops::Range<usize>> {
+    let mut runs: Vec<std::ops::Range<usize>> = vec![];
+    for (col, (old, new)) in previous.iter().zip(current).enumerate() {
+        if old == new {
+            continue;
+        }
+        match runs.last_mut() {
+            Some(run) if col - run.end <= MAX_UNCHANGED_GAP => run.end = col + 1,
+            _ => runs.push(col..col + 1),
+        }
+    }
+    runs
+}
+
+/// number of terminal rows kept free below the screen buffer for the FPS statistics
+pub const STATISTICS_ROWS: u16 = 3;
+/// the smallest screen buffer that still has room for its border and one cell inside it
+const MIN_BUFFER_SIZE: u16 = 3;
+
+/// builds a viewport which fills a terminal of `columns` x `rows` cells, leaving
+/// `STATISTICS_ROWS` rows free at the bottom. The field of view is adjusted to the shape of the
+/// terminal so the scene always fits in it
+pub fn viewport_for_terminal_size((columns, rows): (u16, u16)) -> Viewport {
+    Viewport::new(
+        columns.max(MIN_BUFFER_SIZE) as usize,
+        rows.saturating_sub(STATISTICS_ROWS).max(MIN_BUFFER_SIZE) as usize,
+    )
+    .with_min_fov(MIN_FOV)
+}
+/// queries the size of the terminal through crossterm and builds a viewport which fills it
+pub fn viewport_from_terminal() -> std::io::Result<Viewport> {
+    Ok(viewport_for_terminal_size(crossterm::terminal::size()?))
+}
+/// creates a screen buffer the size of the viewport and draws its border
+pub fn initialize_screen_buffer(viewport: &Viewport) -> ScreenBuffer {
+    let mut screen_buffer = ScreenBuffer::from_viewport(viewport);
+    draw_border(&mut screen_buffer);
     screen_buffer
 }
+/// draws a frame around the outermost cells of the screen buffer
+pub fn draw_border(screen_buffer: &mut ScreenBuffer) {
+    let (width, height) = (screen_buffer.width(), screen_buffer.height());
+    for col in 0..width {
+        screen_buffer.set(0, col, '-');
+        screen_buffer.set(height - 1, col, '-');
+    }
+    for row in 0..height {
+        screen_buffer.set(row, 0, '|');
+        screen_buffer.set(row, width - 1, '|');
+    }
+}
 pub fn clear_screen(stdout: &mut std::io::Stdout) -> std::io::Result<()> {
     queue!(
         stdout,
@@ -46,3 +172,86 @@ pub fn clear_screen(stdout: &mut std::io::Stdout) -> std::io::Result<()> {
     )?;
     Ok(())
 }
+
+/// puts the terminal into raw mode on an alternate screen with a hidden cursor, and restores it
+/// when dropped, including when the main loop exits early with an error
+pub struct InteractiveTerminal {
+    _private: (),
+}
+
+impl InteractiveTerminal {
+    pub fn enter(stdout: &mut std::io::Stdout) -> std::io::Result<InteractiveTerminal> {
+        crossterm::terminal::enable_raw_mode()?;
+        execute!(
+            stdout,
+            crossterm::terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        Ok(InteractiveTerminal { _private: () })
+    }
+}
+
+impl Drop for InteractiveTerminal {
+    fn drop(&mut self) {
+        // errors are ignored, there is nothing sensible left to do with them at this point
+        let _ = execute!(
+            std::io::stdout(),
+            cursor::Show,
+            crossterm::terminal::LeaveAlternateScreen
+        );
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod test_terminal {
+    use super::*;
+    #[test]
+    fn test_changed_runs_merges_small_gaps() {
+        let previous: Vec<Cell> = "aaaaaaaaaaaaaaaaaaaaaaaa"
+            .chars()
+            .map(Cell::plain)
+            .collect();
+        let current: Vec<Cell> = "abaabaaaaaaaaaaaaaaaaaab"
+            .chars()
+            .map(Cell::plain)
+            .collect();
+        assert_eq!(changed_runs(&previous, &current), vec![1..5, 23..24]);
+        assert!(changed_runs(&previous, &previous).is_empty());
+    }
+    #[test]
+    fn test_unchanged_frame_only_emits_synchronization() {
+        let mut screen_buffer = ScreenBuffer::new(4, 2);
+        screen_buffer.set(1, 2, '#');
+        let mut writer = ScreenWriter::new();
+        let mut first_frame = vec![];
+        writer.draw(&mut first_frame, &screen_buffer).unwrap();
+        let mut second_frame = vec![];
+        writer.draw(&mut second_frame, &screen_buffer).unwrap();
+        assert!(second_frame.len() < first_frame.len());
+        assert!(!String::from_utf8(second_frame).unwrap().contains('#'));
+    }
+    #[test]
+    fn test_viewport_leaves_room_for_the_statistics() {
+        let viewport = viewport_for_terminal_size((120, 40));
+        assert_eq!((viewport.width, viewport.height), (120, 37));
+        assert_eq!(viewport.h_w_ratio, DEFAULT_H_W_RATIO);
+        // the scene fits both ways: the vertical field of view is at least the minimum too
+        let vertical_fov = viewport.fov * viewport.h_w_ratio * 37. / 120.;
+        assert!(viewport.fov >= MIN_FOV && vertical_fov >= MIN_FOV - 1e-5);
+    }
+    #[test]
+    fn test_tiny_terminals_get_the_smallest_viewport() {
+        let viewport = viewport_for_terminal_size((80, STATISTICS_ROWS - 1));
+        assert_eq!(
+            (viewport.width, viewport.height),
+            (80, MIN_BUFFER_SIZE as usize)
+        );
+        let viewport = viewport_for_terminal_size((0, 0));
+        let min_size = MIN_BUFFER_SIZE as usize;
+        assert_eq!((viewport.width, viewport.height), (min_size, min_size));
+        assert!(viewport.fov.is_finite());
+        // the border still fits
+        initialize_screen_buffer(&viewport);
+    }
+}
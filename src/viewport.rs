@@ -0,0 +1,190 @@
+use crate::prelude::*;
+use crossterm::style::Color;
+
+/// describes the target a camera renders into: its size in cells, the horizontal field of view
+/// and the aspect ratio of a single cell
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub width: usize,
+    pub height: usize,
+    /// the horizontal field of view, in radians
+    pub fov: f32,
+    /// the ratio of height to width of a single cell. Terminal cells are roughly twice as tall as
+    /// they are wide, image pixels are square
+    pub h_w_ratio: f32,
+}
+
+impl Viewport {
+    pub fn new(width: usize, height: usize) -> Viewport {
+        Viewport {
+            width,
+            height,
+            fov: DEFAULT_FOV,
+            h_w_ratio: DEFAULT_H_W_RATIO,
+        }
+    }
+    pub fn with_fov(mut self, fov: f32) -> Viewport {
+        self.fov = fov;
+        self
+    }
+    pub fn with_h_w_ratio(mut self, h_w_ratio: f32) -> Viewport {
+        self.h_w_ratio = h_w_ratio;
+        self
+    }
+    /// sets the horizontal field of view so that both the horizontal and the vertical field of
+    /// view are at least `min_fov`, so the scene stays in frame whatever the shape of the viewport
+    pub fn with_min_fov(self, min_fov: f32) -> Viewport {
+        let vertical_to_horizontal = self.h_w_ratio * self.height as f32 / self.width as f32;
+        self.with_fov(min_fov.max(min_fov / vertical_to_horizontal))
+    }
+    /// the direction of the ray through the cell at (`row`, `col`), in camera coordinates. The
+    /// result is not normalised
+    pub fn ray_in_camera_frame(&self, row: usize, col: usize) -> Vector {
+        self.ray_through(row as f32, col as f32)
+    }
+    /// like `ray_in_camera_frame`, but through any point of the viewport, so cells can be
+    /// sampled more than once
+    pub fn ray_through(&self, row: f32, col: f32) -> Vector {
+        let angle_per_cell = self.fov / self.width as f32;
+        vector!(
+            angle_per_cell * (col - (self.width / 2) as f32),
+            1,
+            -angle_per_cell * self.h_w_ratio * (row - (self.height / 2) as f32)
+        )
+    }
+}
+
+/// a single terminal cell: a character and the colours it is drawn with. `None` colours leave the
+/// terminal's default colour in place
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+impl Cell {
+    pub const EMPTY: Cell = Cell::plain(' ');
+
+    /// a cell drawn in the terminal's default colours
+    pub const fn plain(glyph: char) -> Cell {
+        Cell {
+            glyph,
+            foreground: None,
+            background: None,
+        }
+    }
+}
+
+impl From<char> for Cell {
+    fn from(glyph: char) -> Cell {
+        Cell::plain(glyph)
+    }
+}
+
+/// a row-major buffer of cells with the same size as a `Viewport`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenBuffer {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl ScreenBuffer {
+    pub fn new(width: usize, height: usize) -> ScreenBuffer {
+        ScreenBuffer {
+            width,
+            height,
+            cells: vec![Cell::EMPTY; width * height],
+        }
+    }
+    pub fn from_viewport(viewport: &Viewport) -> ScreenBuffer {
+        ScreenBuffer::new(viewport.width, viewport.height)
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn get(&self, row: usize, col: usize) -> Cell {
+        self.cells[row * self.width + col]
+    }
+    pub fn set(&mut self, row: usize, col: usize, value: impl Into<Cell>) {
+        self.cells[row * self.width + col] = value.into();
+    }
+    pub fn row(&self, row: usize) -> &[Cell] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width.max(1))
+    }
+    /// the glyphs of every row, without their colours
+    pub fn lines(&self) -> Vec<String> {
+        self.rows()
+            .map(|row| row.iter().map(|cell| cell.glyph).collect())
+            .collect()
+    }
+}
+
+/// the glyphs of the buffer, one line per row, without their colours
+impl std::fmt::Display for ScreenBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test_viewport {
+    use super::*;
+
+    /// the horizontal and vertical spans of the rays through the edges of the viewport, on the
+    /// plane at distance 1 in front of the camera
+    fn spans(viewport: &Viewport) -> (f32, f32) {
+        let (width, height) = (viewport.width, viewport.height);
+        let (row, col) = (height / 2, width / 2);
+        let horizontal =
+            viewport.ray_in_camera_frame(row, width).x - viewport.ray_in_camera_frame(row, 0).x;
+        let vertical =
+            viewport.ray_in_camera_frame(0, col).z - viewport.ray_in_camera_frame(height, col).z;
+        (horizontal, vertical)
+    }
+    #[test]
+    fn test_central_ray_is_the_camera_axis() {
+        let viewport = Viewport::new(21, 9);
+        assert_eq!(viewport.ray_in_camera_frame(4, 10), vector!(0, 1, 0));
+        // rows go down the screen and columns to the right
+        let corner = viewport.ray_in_camera_frame(0, 0);
+        assert!(corner.x < 0. && corner.z > 0. && corner.y == 1.);
+    }
+    #[test]
+    fn test_edge_rays_span_the_fov() {
+        let viewport = Viewport::new(40, 20).with_fov(0.8).with_h_w_ratio(1.);
+        let (horizontal, vertical) = spans(&viewport);
+        assert!((horizontal - 0.8).abs() < 1e-5);
+        assert!((vertical - 0.4).abs() < 1e-5);
+    }
+    #[test]
+    fn test_min_fov_widens_the_longer_axis() {
+        let wide = Viewport::new(80, 24).with_min_fov(0.5);
+        let (horizontal, vertical) = spans(&wide);
+        assert!((vertical - 0.5).abs() < 1e-5);
+        assert!(horizontal > 0.5);
+        let tall = Viewport::new(10, 40).with_h_w_ratio(1.).with_min_fov(0.5);
+        let (horizontal, vertical) = spans(&tall);
+        assert!((horizontal - 0.5).abs() < 1e-5);
+        assert!((vertical - 2.).abs() < 1e-5);
+    }
+    #[test]
+    fn test_h_w_ratio_stretches_rows() {
+        let square = Viewport::new(20, 10).with_h_w_ratio(1.);
+        let tall = square.with_h_w_ratio(2.);
+        let (square_ray, tall_ray) = (
+            square.ray_in_camera_frame(2, 3),
+            tall.ray_in_camera_frame(2, 3),
+        );
+        assert_eq!(square_ray.x, tall_ray.x);
+        assert_eq!(2. * square_ray.z, tall_ray.z);
+        assert!((2. * spans(&square).1 - spans(&tall).1).abs() < 1e-5);
+    }
+}
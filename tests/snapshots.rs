@@ -0,0 +1,143 @@
+//! golden frames of canonical scenes, rendered to text and compared against the snapshots checked
+//! in under `tests/snapshots`. After an intended change to the rendering, regenerate them with
+//!
+//!     UPDATE_SNAPSHOTS=1 cargo test --test snapshots
+//!
+//! and review the diff of the snapshot files
+use spinner::prelude::*;
+use std::path::PathBuf;
+
+const WIDTH: usize = 48;
+const HEIGHT: usize = 20;
+
+/// every line of a snapshot file ends with this, so editors do not strip the trailing spaces
+const LINE_END: char = '|';
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{}.txt", name))
+}
+
+/// renders the object seen from `camera` and compares the frame to the snapshot called `name`
+fn assert_snapshot(name: &str, camera: &Camera, object: &impl Object3D) {
+    let viewport = Viewport::new(WIDTH, HEIGHT).with_min_fov(0.45);
+    let lines =
+        OffscreenRenderer::default().render_lines(camera, &Scene::default(), object, &viewport);
+    let frame: String = lines
+        .iter()
+        .map(|line| format!("{}{}\n", line, LINE_END))
+        .collect();
+    let path = snapshot_path(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &frame).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no snapshot at {}, create it with UPDATE_SNAPSHOTS=1",
+            path.display()
+        )
+    });
+    assert!(
+        frame == expected,
+        "the frame of `{}` differs from its snapshot\n\nexpected:\n{}\nrendered:\n{}\n\
+         regenerate the snapshots with UPDATE_SNAPSHOTS=1 if the change is intended",
+        name,
+        expected,
+        frame
+    );
+}
+
+/// the camera all the small scenes are seen from, above and to the front left
+fn default_camera() -> Camera {
+    Camera::look_at(vector!(-5, -9, 4), vector!(0, 0, 0), unit_z())
+}
+
+/// a rotation by `angle` radians around the normalised `axis`
+fn rotation(axis: Vector, angle: f32) -> Matrix {
+    nalgebra::Rotation3::from_axis_angle(&nalgebra::Unit::new_normalize(axis), angle).into_inner()
+}
+
+fn two_spheres() -> Vec<Box<dyn Object3D>> {
+    boxed_vec![
+        sphere::Sphere::new(vector!(-0.8, 0, 0), 1.5),
+        sphere::Sphere::new(vector!(0.8, 0, 0), 1.5)
+    ]
+}
+
+#[test]
+fn sphere() {
+    assert_snapshot(
+        "sphere",
+        &default_camera(),
+        &sphere::Sphere::new(vector!(0, 0, 0), 2.),
+    );
+}
+
+#[test]
+fn cuboid_axis_aligned() {
+    assert_snapshot(
+        "cuboid_axis_aligned",
+        &default_camera(),
+        &cuboid::Cuboid::new(1., 2., 3.),
+    );
+}
+
+#[test]
+fn cuboid_rotated_about_z() {
+    let mut cuboid = cuboid::Cuboid::new(1., 2., 3.);
+    cuboid.set_orientation_matrix(&rotation(unit_z(), std::f32::consts::FRAC_PI_6));
+    assert_snapshot("cuboid_rotated_about_z", &default_camera(), &cuboid);
+}
+
+#[test]
+fn cuboid_rotated_about_diagonal() {
+    let mut cuboid = cuboid::Cuboid::new(1., 2., 3.);
+    cuboid.set_orientation_matrix(&rotation(vector!(1, 1, 1), 1.));
+    assert_snapshot("cuboid_rotated_about_diagonal", &default_camera(), &cuboid);
+}
+
+#[test]
+fn pp() {
+    let camera = Camera::look_at(vector!(-16, -30, 14), vector!(0, 0, 4), unit_z());
+    assert_snapshot("pp", &camera, &pp::PP::default());
+}
+
+#[test]
+fn union() {
+    assert_snapshot(
+        "union",
+        &default_camera(),
+        &Union::from_objects(two_spheres()),
+    );
+}
+
+#[test]
+fn intersection() {
+    assert_snapshot(
+        "intersection",
+        &default_camera(),
+        &Intersection::from_objects(two_spheres()),
+    );
+}
+
+#[test]
+fn soft_union() {
+    assert_snapshot(
+        "soft_union",
+        &default_camera(),
+        &SoftUnion::from_objects(two_spheres(), 0.2),
+    );
+}
+
+#[test]
+fn soft_intersection() {
+    assert_snapshot(
+        "soft_intersection",
+        &default_camera(),
+        &SoftIntersection::from_objects(two_spheres(), 0.2),
+    );
+}